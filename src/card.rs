@@ -1,4 +1,10 @@
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+use crate::errors::ParseCardError;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Suit {
     Clubs,
     Diamonds,
@@ -17,7 +23,9 @@ impl Suit {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Rank {
     Two,
     Three,
@@ -54,17 +62,46 @@ impl Rank {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Card {
     suit: Suit,
     rank: Rank,
 }
 
+// Suits and ranks in index order, matching their declaration order
+const SUIT_ORDER: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+const RANK_ORDER: [Rank; 13] = [
+    Rank::Two,
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+    Rank::Ace,
+];
+
 impl Card {
     pub fn new(suit: Suit, rank: Rank) -> Card {
         Card { suit, rank }
     }
 
+    // A number from 0 to 51, grouped by suit from clubs to spades and by rank within a suit
+    pub fn index(&self) -> usize {
+        self.suit as usize * 13 + self.rank as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Card> {
+        let suit = *SUIT_ORDER.get(index / 13)?;
+
+        Some(Card::new(suit, RANK_ORDER[index % 13]))
+    }
+
     pub fn suit(&self) -> &Suit {
         &self.suit
     }
@@ -85,6 +122,186 @@ impl Card {
     }
 }
 
+// The ways cards can be written. Display uses Ascii, parsing accepts all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Notation {
+    // Rank then suit, "AS" or "TD"
+    #[default]
+    Ascii,
+    // Suit symbol then rank, "♠A" or "♦T"
+    Unicode,
+    // Swedish names, suit then rank, "spader ess" or "ruter tio"
+    Swedish,
+}
+
+// Indexed like SUIT_ORDER: letter, symbol and Swedish name
+const SUIT_NAMES: [(&str, &str, &str); 4] = [
+    ("C", "♣", "klöver"),
+    ("D", "♦", "ruter"),
+    ("H", "♥", "hjärter"),
+    ("S", "♠", "spader"),
+];
+
+// Indexed like RANK_ORDER: symbol and Swedish name
+const RANK_NAMES: [(&str, &str); 13] = [
+    ("2", "två"),
+    ("3", "tre"),
+    ("4", "fyra"),
+    ("5", "fem"),
+    ("6", "sex"),
+    ("7", "sju"),
+    ("8", "åtta"),
+    ("9", "nio"),
+    ("T", "tio"),
+    ("J", "knekt"),
+    ("Q", "dam"),
+    ("K", "kung"),
+    ("A", "ess"),
+];
+
+impl Suit {
+    pub fn format(&self, notation: Notation) -> &'static str {
+        let (letter, symbol, name) = SUIT_NAMES[*self as usize];
+
+        match notation {
+            Notation::Ascii => letter,
+            Notation::Unicode => symbol,
+            Notation::Swedish => name,
+        }
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(Notation::Ascii))
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(text: &str) -> Result<Suit, ParseCardError> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Err(ParseCardError::Empty);
+        }
+
+        SUIT_NAMES.iter()
+            .position(|(letter, symbol, name)| {
+                text == letter.to_lowercase() || text == *symbol || text == *name
+            })
+            .map(|i| SUIT_ORDER[i])
+            .ok_or(ParseCardError::InvalidSuit)
+    }
+}
+
+impl Rank {
+    // Ascii and Unicode share the same rank symbols
+    pub fn format(&self, notation: Notation) -> &'static str {
+        let (symbol, name) = RANK_NAMES[*self as usize];
+
+        match notation {
+            Notation::Ascii | Notation::Unicode => symbol,
+            Notation::Swedish => name,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(Notation::Ascii))
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    // Ten may also be written as "10"
+    fn from_str(text: &str) -> Result<Rank, ParseCardError> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Err(ParseCardError::Empty);
+        }
+        if text == "10" {
+            return Ok(Rank::Ten);
+        }
+
+        RANK_NAMES.iter()
+            .position(|(symbol, name)| text == symbol.to_lowercase() || text == *name)
+            .map(|i| RANK_ORDER[i])
+            .ok_or(ParseCardError::InvalidRank)
+    }
+}
+
+impl Card {
+    pub fn format(&self, notation: Notation) -> String {
+        match notation {
+            Notation::Ascii => format!("{}{}", self.rank.format(notation), self.suit.format(notation)),
+            Notation::Unicode => format!("{}{}", self.suit.format(notation), self.rank.format(notation)),
+            Notation::Swedish => format!("{} {}", self.suit.format(notation), self.rank.format(notation)),
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(Notation::Ascii))
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    // Reads every notation, ignoring case. A suit symbol may also come after the rank, like "A♠"
+    fn from_str(text: &str) -> Result<Card, ParseCardError> {
+        let text = text.trim();
+
+        if let Some((suit, rank)) = text.split_once(char::is_whitespace) {
+            return Ok(Card::new(suit.parse()?, rank.parse()?));
+        }
+
+        let first = text.chars().next().ok_or(ParseCardError::Empty)?;
+        let (suit, rank) = match SUIT_NAMES.iter().any(|(_, symbol, _)| symbol.starts_with(first)) {
+            true => text.split_at(first.len_utf8()),
+            false => {
+                let last = text.chars().next_back().ok_or(ParseCardError::Empty)?;
+                let (rank, suit) = text.split_at(text.len() - last.len_utf8());
+                (suit, rank)
+            }
+        };
+
+        Ok(Card::new(suit.parse()?, rank.parse()?))
+    }
+}
+
+// Cards are serialized in their compact form, rank then suit, like "AS" or "10H",
+// and read in any notation
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{Card, Rank};
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    impl Serialize for Card {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.rank() {
+                Rank::Ten => serializer.serialize_str(&format!("10{}", self.suit())),
+                _ => serializer.collect_str(self),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Card {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+            let text = String::deserialize(deserializer)?;
+
+            text.parse().map_err(de::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +392,79 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn index_round_trips() {
+        assert_eq!(Card::new(Suit::Clubs, Rank::Two).index(), 0);
+        assert_eq!(Card::new(Suit::Spades, Rank::Ace).index(), 51);
+        assert!((0..52).all(|i| Card::from_index(i).map(|c| c.index()) == Some(i)));
+        assert_eq!(Card::from_index(52), None);
+    }
+
+    #[test]
+    fn formats_in_every_notation() {
+        let card = Card::new(Suit::Spades, Rank::Ace);
+        let ten = Card::new(Suit::Diamonds, Rank::Ten);
+
+        assert_eq!(card.to_string(), "AS");
+        assert_eq!(ten.to_string(), "TD");
+        assert_eq!(card.format(Notation::Unicode), "♠A");
+        assert_eq!(card.format(Notation::Swedish), "spader ess");
+        assert_eq!(Card::new(Suit::Hearts, Rank::King).format(Notation::Swedish), "hjärter kung");
+    }
+
+    #[test]
+    fn parses_every_notation() {
+        let card = Card::new(Suit::Spades, Rank::Ace);
+        let ten = Card::new(Suit::Diamonds, Rank::Ten);
+
+        assert_eq!("AS".parse(), Ok(card));
+        assert_eq!("as".parse(), Ok(card));
+        assert_eq!("♠A".parse(), Ok(card));
+        assert_eq!("A♠".parse(), Ok(card));
+        assert_eq!(" Spader Ess ".parse(), Ok(card));
+        assert_eq!("TD".parse(), Ok(ten));
+        assert_eq!("10D".parse(), Ok(ten));
+        assert_eq!("♦10".parse(), Ok(ten));
+        assert_eq!("ruter tio".parse(), Ok(ten));
+    }
+
+    #[test]
+    fn formatted_cards_parse_back() {
+        for index in 0..52 {
+            let card = Card::from_index(index).unwrap();
+
+            for notation in [Notation::Ascii, Notation::Unicode, Notation::Swedish] {
+                assert_eq!(card.format(notation).parse(), Ok(card));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+        assert_eq!("1H".parse::<Card>(), Err(ParseCardError::InvalidRank));
+        assert_eq!("AX".parse::<Card>(), Err(ParseCardError::InvalidSuit));
+        assert_eq!("A".parse::<Card>(), Err(ParseCardError::InvalidSuit));
+        assert_eq!("spader".parse::<Card>(), Err(ParseCardError::InvalidSuit));
+        assert_eq!("spader prins".parse::<Card>(), Err(ParseCardError::InvalidRank));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_to_compact_form() {
+        assert_eq!(serde_json::to_string(&Card::new(Suit::Spades, Rank::Ace)).unwrap(), "\"AS\"");
+        assert_eq!(serde_json::to_string(&Card::new(Suit::Hearts, Rank::Ten)).unwrap(), "\"10H\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_from_compact_form() {
+        let card: Card = serde_json::from_str("\"10h\"").unwrap();
+
+        assert_eq!(card, Card::new(Suit::Hearts, Rank::Ten));
+        assert!(serde_json::from_str::<Card>("\"1H\"").is_err());
+        assert!(serde_json::from_str::<Card>("\"A\"").is_err());
+        assert!(serde_json::from_str::<Card>("\"\"").is_err());
+    }
 }
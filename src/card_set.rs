@@ -0,0 +1,319 @@
+use crate::card::{Card, Suit};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Sub};
+
+const SUIT_BITS: u64 = (1 << 13) - 1;
+const ALL_BITS: u64 = (1 << 52) - 1;
+
+// A set of cards packed into the low 52 bits of a u64, one bit per card at its
+// Card::index, so every suit is a run of thirteen bits from the two up to the ace
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub fn new() -> CardSet {
+        CardSet(0)
+    }
+
+    pub fn full() -> CardSet {
+        CardSet(ALL_BITS)
+    }
+
+    // All thirteen cards of a suit
+    pub fn of_suit(suit: Suit) -> CardSet {
+        CardSet(SUIT_BITS << (suit as u64 * 13))
+    }
+
+    // Bits above the 52nd are not cards and are dropped
+    pub fn from_bits(bits: u64) -> CardSet {
+        CardSet(bits & ALL_BITS)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    // Returns false if the card was already in the set
+    pub fn insert(&mut self, card: Card) -> bool {
+        let added = !self.contains(card);
+        self.0 |= Self::bit(card);
+
+        added
+    }
+
+    // Returns false if the card was not in the set
+    pub fn remove(&mut self, card: Card) -> bool {
+        let removed = self.contains(card);
+        self.0 &= !Self::bit(card);
+
+        removed
+    }
+
+    pub fn contains(&self, card: Card) -> bool {
+        self.0 & Self::bit(card) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(&self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+
+    pub fn difference(&self, other: CardSet) -> CardSet {
+        CardSet(self.0 & !other.0)
+    }
+
+    // The cards of the set in the given suit
+    pub fn suit(&self, suit: Suit) -> CardSet {
+        self.intersection(Self::of_suit(suit))
+    }
+
+    pub fn has_suit(&self, suit: Suit) -> bool {
+        !self.suit(suit).is_empty()
+    }
+
+    // Highest and lowest go by suit before rank, use them on a single suit to get its top or bottom card
+    pub fn highest(&self) -> Option<Card> {
+        self.iter().next_back()
+    }
+
+    pub fn lowest(&self) -> Option<Card> {
+        self.iter().next()
+    }
+
+    // Clubs first and spades last, each suit from the two up to the ace
+    pub fn iter(&self) -> Iter {
+        Iter { bits: self.0 }
+    }
+
+    fn bit(card: Card) -> u64 {
+        1 << card.index()
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        self.union(other)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        self.intersection(other)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        self.difference(other)
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(cards: I) -> CardSet {
+        let mut set = CardSet::new();
+        set.extend(cards);
+
+        set
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, cards: I) {
+        for card in cards {
+            self.insert(card);
+        }
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Iter {
+    bits: u64,
+}
+
+impl Iterator for Iter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        let index = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+
+        Card::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter {
+    fn next_back(&mut self) -> Option<Card> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        let index = 63 - self.bits.leading_zeros() as usize;
+        self.bits &= !(1 << index);
+
+        Card::from_index(index)
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+// Serialized as a list of cards in iteration order
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::CardSet;
+    use crate::card::Card;
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    impl Serialize for CardSet {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CardSet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CardSet, D::Error> {
+            Ok(Vec::<Card>::deserialize(deserializer)?.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Rank;
+
+    fn setup_set() -> CardSet {
+        [
+            Card::new(Suit::Spades, Rank::Ace),
+            Card::new(Suit::Hearts, Rank::Two),
+            Card::new(Suit::Spades, Rank::Four),
+        ].into_iter().collect()
+    }
+
+    #[test]
+    fn new_set_is_empty() {
+        let set = CardSet::new();
+
+        assert!(set.is_empty());
+        assert_eq!(set.len(), 0);
+        assert_eq!(CardSet::full().len(), 52);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut set = CardSet::new();
+        let card = Card::new(Suit::Diamonds, Rank::Queen);
+
+        assert!(set.insert(card));
+        assert!(!set.insert(card));
+        assert!(set.contains(card));
+        assert_eq!(set.len(), 1);
+
+        assert!(set.remove(card));
+        assert!(!set.remove(card));
+        assert!(!set.contains(card));
+    }
+
+    #[test]
+    fn iterates_by_suit_then_rank() {
+        assert_eq!(
+            setup_set().iter().collect::<Vec<Card>>(),
+            vec![
+                Card::new(Suit::Hearts, Rank::Two),
+                Card::new(Suit::Spades, Rank::Four),
+                Card::new(Suit::Spades, Rank::Ace),
+            ],
+        );
+        assert_eq!(CardSet::full().iter().len(), 52);
+        assert_eq!(CardSet::full().iter().next_back(), Some(Card::new(Suit::Spades, Rank::Ace)));
+    }
+
+    #[test]
+    fn suit_masks() {
+        let set = setup_set();
+
+        assert_eq!(set.suit(Suit::Spades).len(), 2);
+        assert!(set.has_suit(Suit::Hearts));
+        assert!(!set.has_suit(Suit::Clubs));
+        assert_eq!(set.suit(Suit::Spades).highest(), Some(Card::new(Suit::Spades, Rank::Ace)));
+        assert_eq!(set.suit(Suit::Spades).lowest(), Some(Card::new(Suit::Spades, Rank::Four)));
+        assert_eq!(set.suit(Suit::Clubs).highest(), None);
+        assert!(Suit::all().into_iter().all(|suit| CardSet::of_suit(suit).len() == 13));
+    }
+
+    #[test]
+    fn set_operations() {
+        let set = setup_set();
+        let spades = CardSet::of_suit(Suit::Spades);
+
+        assert_eq!(set & spades, set.suit(Suit::Spades));
+        assert_eq!((set | spades).len(), 14);
+        assert_eq!((set - spades).iter().collect::<Vec<Card>>(), vec![Card::new(Suit::Hearts, Rank::Two)]);
+        assert_eq!(CardSet::full() - CardSet::full(), CardSet::new());
+    }
+
+    #[test]
+    fn bits_outside_the_deck_are_dropped() {
+        assert_eq!(CardSet::from_bits(u64::MAX), CardSet::full());
+        assert_eq!(CardSet::from_bits(setup_set().bits()), setup_set());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_a_list_of_cards() {
+        let json = serde_json::to_string(&setup_set()).unwrap();
+
+        assert_eq!(json, "[\"2H\",\"4S\",\"AS\"]");
+        assert_eq!(serde_json::from_str::<CardSet>(&json).unwrap(), setup_set());
+    }
+}
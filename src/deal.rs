@@ -0,0 +1,140 @@
+use crate::card_set::CardSet;
+use crate::errors::GameError;
+use crate::hand::Hand;
+
+// The number of different deals, 52! / (13!)^4
+pub const DEAL_COUNT: u128 = 53_644_737_765_488_792_839_237_440_000;
+
+// Every deal is numbered from 0 up to DEAL_COUNT. Going through the cards in index
+// order, the deals are ordered by which seat holds the card, north first, so that
+// the number of a deal is how many deals come before it in that order.
+// Hands are indexed by seat, like the hands dealt from a Deck.
+pub fn deal_index(hands: &[Hand]) -> Result<u128, GameError> {
+    check(hands)?;
+
+    let mut left = [13u128; 4];
+    let mut remaining = 52u128;
+    let mut ways = DEAL_COUNT;
+    let mut index = 0;
+
+    for card in CardSet::full() {
+        let holder = hands.iter()
+            .position(|hand| hand.contains(&card))
+            .expect("A checked deal should hold every card");
+
+        // Deals giving the card to an earlier seat all come first
+        index += (0..holder).map(|seat| ways * left[seat] / remaining).sum::<u128>();
+        ways = ways * left[holder] / remaining;
+        left[holder] -= 1;
+        remaining -= 1;
+    }
+
+    Ok(index)
+}
+
+pub fn deal_from_index(mut index: u128) -> Result<Vec<Hand>, GameError> {
+    if index >= DEAL_COUNT {
+        return Err(GameError::InvalidDeal);
+    }
+
+    let mut hands = vec![Hand::new(); 4];
+    let mut left = [13u128; 4];
+    let mut remaining = 52u128;
+    let mut ways = DEAL_COUNT;
+
+    for card in CardSet::full() {
+        for seat in 0..4 {
+            let with_seat = ways * left[seat] / remaining;
+            if index < with_seat {
+                hands[seat].add_card(card);
+                ways = with_seat;
+                left[seat] -= 1;
+                break;
+            }
+            index -= with_seat;
+        }
+        remaining -= 1;
+    }
+
+    Ok(hands)
+}
+
+// Four hands of thirteen cards that together make up the whole deck
+pub fn check(hands: &[Hand]) -> Result<(), GameError> {
+    let cards = hands.iter().fold(CardSet::new(), |cards, hand| cards | hand.card_set());
+
+    if hands.len() != 4 || hands.iter().any(|hand| hand.len() != 13) || cards != CardSet::full() {
+        return Err(GameError::InvalidDeal);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::deck::Deck;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn binomial(n: u128, k: u128) -> u128 {
+        (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+    }
+
+    fn shuffled_hands(seed: u64) -> Vec<Hand> {
+        let mut deck = Deck::new();
+        deck.shuffle_with(&mut StdRng::seed_from_u64(seed));
+
+        deck.deal_hands()
+    }
+
+    #[test]
+    fn deal_count_is_the_number_of_deals() {
+        assert_eq!(DEAL_COUNT, binomial(52, 13) * binomial(39, 13) * binomial(26, 13));
+    }
+
+    #[test]
+    fn first_and_last_deals() {
+        let first = deal_from_index(0).unwrap();
+        let last = deal_from_index(DEAL_COUNT - 1).unwrap();
+
+        // The lowest cards go to north first, the highest to west last
+        assert!(first[0].contains(&Card::from_index(0).unwrap()));
+        assert!(first[3].contains(&Card::from_index(51).unwrap()));
+        assert!(last[3].contains(&Card::from_index(0).unwrap()));
+        assert!(last[0].contains(&Card::from_index(51).unwrap()));
+        assert_eq!(deal_index(&first), Ok(0));
+        assert_eq!(deal_index(&last), Ok(DEAL_COUNT - 1));
+    }
+
+    #[test]
+    fn index_round_trips() {
+        for seed in 0..20 {
+            let hands = shuffled_hands(seed);
+            let index = deal_index(&hands).unwrap();
+
+            assert!(index < DEAL_COUNT);
+            assert_eq!(deal_from_index(index), Ok(hands));
+        }
+
+        for index in [1, 12_345_678_901_234_567_890, DEAL_COUNT / 2] {
+            assert_eq!(deal_index(&deal_from_index(index).unwrap()), Ok(index));
+        }
+    }
+
+    #[test]
+    fn denies_invalid_deals() {
+        let mut hands = shuffled_hands(1);
+        let card = hands[0].cards()[0];
+
+        assert_eq!(deal_from_index(DEAL_COUNT), Err(GameError::InvalidDeal));
+        assert_eq!(deal_index(&hands[..3]), Err(GameError::InvalidDeal));
+
+        hands[0].remove_card(&card);
+        assert_eq!(deal_index(&hands), Err(GameError::InvalidDeal));
+
+        hands[1].add_card(card);
+        assert_eq!(deal_index(&hands), Err(GameError::InvalidDeal));
+    }
+}
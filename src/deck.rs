@@ -4,8 +4,11 @@ use crate::card::{
     Rank,
 };
 use crate::hand::Hand;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck {
     pub cards: Vec<Card>,
 }
@@ -27,11 +30,16 @@ impl Deck {
         self.cards.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     pub fn shuffle(&mut self) {
-        use rand::seq::SliceRandom;
-        use rand::thread_rng;
+        self.shuffle_with(&mut rand::thread_rng());
+    }
 
-        self.cards.shuffle(&mut thread_rng());
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
     pub fn deal_hands(&mut self) -> Vec<Hand> {
@@ -47,6 +55,12 @@ impl Deck {
     }
 }
 
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,6 +92,20 @@ mod tests {
         assert_ne!(deck1.cards, deck2.cards);
     }
 
+    #[test]
+    fn shuffle_with_same_seed_is_reproducible() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut deck1 = Deck::new();
+        let mut deck2 = Deck::new();
+
+        deck1.shuffle_with(&mut StdRng::seed_from_u64(7));
+        deck2.shuffle_with(&mut StdRng::seed_from_u64(7));
+
+        assert_eq!(deck1.cards, deck2.cards);
+    }
+
     #[test]
     fn deal_hands() {
         let mut deck = Deck::new();
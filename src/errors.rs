@@ -1,5 +1,175 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum GameError {
     RequiresFourReadyPlayers,
     UnbalancedTeams,
+    LobbyFull,
+    AlreadyJoined,
+    NotInLobby,
+    TeamFull,
+    AlreadyStarted,
+    NotHost,
+    LobbyLocked,
+    LobbyNotFound,
+    Spectating,
+    NotSpectating,
+    NotInGame,
+    InvalidTarget,
+    InvalidTurnTimeLimit,
+    WrongSeatingMode,
+    SeatTaken,
+    SeatsNotChosen,
+    BiddingNotFinished,
+    BiddingFinished,
+    AlreadyBid,
+    DealerMustPlay,
+    NotPlayersTurn,
+    CardNotInHand,
+    MustFollowSuit,
+    RoundFinished,
+    RoundNotFinished,
+    MatchFinished,
+    RoundInProgress,
+    NotBidding,
+    NotPlaying,
+    InvalidClaim,
+    NotStarted,
+    ReplayMismatch,
+    InvalidDeal,
+    InvalidTable,
+    MissingSeed,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            GameError::RequiresFourReadyPlayers => "the game needs four ready players to start",
+            GameError::UnbalancedTeams => "both teams need two players",
+            GameError::LobbyFull => "the lobby is full",
+            GameError::AlreadyJoined => "the user has already joined the lobby",
+            GameError::NotInLobby => "the user is not in the lobby",
+            GameError::TeamFull => "the team already has two players",
+            GameError::AlreadyStarted => "the game has already started",
+            GameError::NotHost => "only the host can do that",
+            GameError::LobbyLocked => "the lobby is locked",
+            GameError::LobbyNotFound => "no lobby was found",
+            GameError::Spectating => "spectators can't take part in the game",
+            GameError::NotSpectating => "the user is not spectating",
+            GameError::NotInGame => "the user is not playing in the game",
+            GameError::InvalidTarget => "the target score must be above zero",
+            GameError::InvalidTurnTimeLimit => "the turn time limit must be above zero",
+            GameError::WrongSeatingMode => "not possible with the lobby's seating mode",
+            GameError::SeatTaken => "the seat is already taken",
+            GameError::SeatsNotChosen => "every player needs to choose a seat",
+            GameError::BiddingNotFinished => "the bidding is not finished",
+            GameError::BiddingFinished => "the bidding is already finished",
+            GameError::AlreadyBid => "the player has already bid",
+            GameError::DealerMustPlay => "nolo is disabled, the dealer must play",
+            GameError::NotPlayersTurn => "it is not the player's turn",
+            GameError::CardNotInHand => "the card is not in the player's hand",
+            GameError::MustFollowSuit => "the player must follow the led suit",
+            GameError::RoundFinished => "the round is already finished",
+            GameError::RoundNotFinished => "the round is not finished",
+            GameError::MatchFinished => "the match is already finished",
+            GameError::RoundInProgress => "a round is already in progress",
+            GameError::NotBidding => "no bidding is in progress",
+            GameError::NotPlaying => "no round is being played",
+            GameError::InvalidClaim => "the claim can't be guaranteed",
+            GameError::NotStarted => "the game has not started",
+            GameError::ReplayMismatch => "the recorded moves don't match the replayed game",
+            GameError::InvalidDeal => "a deal needs four hands of thirteen different cards",
+            GameError::InvalidTable => "a table needs four different players, partners across from each other",
+            GameError::MissingSeed => "a started game needs its seed",
+        };
+
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for GameError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ParseCardError {
+    Empty,
+    InvalidSuit,
+    InvalidRank,
+    DuplicateCard,
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ParseCardError::Empty => "there is nothing to read",
+            ParseCardError::InvalidSuit => "not a known suit",
+            ParseCardError::InvalidRank => "not a known rank",
+            ParseCardError::DuplicateCard => "the same card is given twice",
+        };
+
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PbnError {
+    Syntax,
+    MissingTag,
+    InvalidValue,
+    Card(ParseCardError),
+    // The recorded deal can't be played out the way it is written
+    Game(GameError),
+}
+
+impl fmt::Display for PbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PbnError::Syntax => write!(f, "a line is neither a tag nor part of a section"),
+            PbnError::MissingTag => write!(f, "a required tag is missing"),
+            PbnError::InvalidValue => write!(f, "a tag has an invalid value"),
+            PbnError::Card(error) => write!(f, "invalid card: {}", error),
+            PbnError::Game(error) => write!(f, "the deal can't be replayed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PbnError {}
+
+impl From<ParseCardError> for PbnError {
+    fn from(error: ParseCardError) -> PbnError {
+        PbnError::Card(error)
+    }
+}
+
+impl From<GameError> for PbnError {
+    fn from(error: GameError) -> PbnError {
+        PbnError::Game(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(GameError::LobbyFull.to_string(), "the lobby is full");
+        assert_eq!(GameError::MustFollowSuit.to_string(), "the player must follow the led suit");
+        assert_eq!(ParseCardError::InvalidRank.to_string(), "not a known rank");
+        assert_eq!(PbnError::Card(ParseCardError::InvalidSuit).to_string(), "invalid card: not a known suit");
+    }
+
+    #[test]
+    fn is_std_error() {
+        let error: Box<dyn std::error::Error> = Box::new(GameError::TeamFull);
+
+        assert_eq!(error.to_string(), "the team already has two players");
+    }
 }
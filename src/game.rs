@@ -1,118 +1,1104 @@
-use crate::game::bid_round::BidRound;
+use crate::card::Card;
+use crate::errors::GameError;
+use crate::game::bid_round::{Bid, BidRound};
+use crate::game::contract::Contract;
+use crate::game::play_round::PlayRound;
+use crate::game::score::{DealScore, Score};
+use crate::game::seat::Seat;
 use crate::user::User;
-use crate::game::lobby::Lobby;
+use std::sync::Arc;
+use std::time::Duration;
 use crate::game::table::Table;
+use crate::game::view::{PlayerView, SpectatorView};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 pub mod lobby;
 pub mod table;
 pub mod bid_round;
+pub mod play_round;
+pub mod contract;
+pub mod score;
+pub mod seat;
+pub mod registry;
+pub mod view;
+pub mod session;
+pub mod event_log;
+pub mod pbn;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Settings {
     pub to_win: u8,
+    // Seeds every random decision of the match, a random seed is picked when unset
+    pub seed: Option<u64>,
+    pub seating: SeatingMode,
+    // When everyone passes the deal is played as nolo, otherwise the dealer has to play
+    pub nolo: bool,
+    pub scoring: ScoringScheme,
+    // Enforced by whoever drives the game, the rules themselves don't keep time
+    pub turn_time_limit: Option<Duration>,
+    pub spectators: SpectatorMode,
+}
+
+impl Settings {
+    // Classic Swedish whist, first to 13 with nolo and doubled penalties
+    pub fn classic() -> Settings {
+        Settings {
+            to_win: 13,
+            seed: None,
+            seating: SeatingMode::Draw,
+            nolo: true,
+            scoring: ScoringScheme::Classic,
+            turn_time_limit: None,
+            spectators: SpectatorMode::Hidden,
+        }
+    }
+
+    // A quicker match for when there is no time for a full one
+    pub fn short_game() -> Settings {
+        Settings {
+            to_win: 5,
+            scoring: ScoringScheme::Flat,
+            ..Settings::classic()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.to_win == 0 {
+            return Err(GameError::InvalidTarget);
+        }
+
+        if self.turn_time_limit.is_some_and(|limit| limit.is_zero()) {
+            return Err(GameError::InvalidTurnTimeLimit);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::classic()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScoringScheme {
+    // A failed grand gives the opponents double their tricks beyond book
+    Classic,
+    // Every deal scores the tricks beyond book, failed grand or not
+    Flat,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SeatingMode {
+    // Seats and the first dealer are drawn when the game starts
+    Draw,
+    // Players pick their own seats in the lobby, teams follow from the seats
+    Choose,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Team {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SpectatorMode {
+    // Spectators follow the play but never see a hand
+    Hidden,
+    // Spectators see every hand, but only once the play is this many tricks further along
+    Kibitzer { delay: u8 },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Team {
     Lajvarna,
     Gottarna,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Player<'a> {
-    user: &'a User,
+impl Team {
+    pub fn opponent(&self) -> Team {
+        match self {
+            Team::Lajvarna => Team::Gottarna,
+            Team::Gottarna => Team::Lajvarna,
+        }
+    }
+
+    // The team a chosen seat belongs to, north and south play for Lajvarna.
+    // Drawn seats can put either team at north and south, so go by Table::team_at once seated
+    pub(crate) fn of_chosen_seat(seat: Seat) -> Team {
+        match seat {
+            Seat::North | Seat::South => Team::Lajvarna,
+            Seat::East | Seat::West => Team::Gottarna,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Player {
+    user: Arc<User>,
     team: Team,
+    seat: Option<Seat>,
     ready: bool,
 }
 
-impl<'a> Player<'a> {
-    fn build(user: &'a User, team: Team) -> Player<'a> {
+impl Player {
+    fn build(user: Arc<User>, team: Team) -> Player {
         Player {
             user,
             team,
+            seat: None,
             ready: false,
         }
     }
 
-    fn user(&self) -> &User {
-        self.user
+    pub fn user(&self) -> &User {
+        &self.user
     }
 
-    fn team(&self) -> Team {
+    pub fn team(&self) -> Team {
         self.team
     }
+
+    pub fn seat(&self) -> Option<Seat> {
+        self.seat
+    }
+
+    fn seated(&self, seat: Seat) -> Player {
+        Player { seat: Some(seat), ..self.clone() }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
 }
 
+// Where the match stands, each phase holds what is needed to continue from it.
+// Seating and dealt phases wait for the dealer, bidding and playing for the seat in turn.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Phase {
+    // Seats are drawn and nothing has been dealt yet
+    Seating,
+    // The dealer is about to deal the next hand
+    Dealing,
+    Bidding(BidRound),
+    Playing(PlayRound),
+    // The last deal has been scored, the next dealer deals when ready
+    DealScored(DealScore),
+    // Conceded when the loser gave up, the deal in progress then was never scored
+    MatchOver { winner: Team, conceded: bool },
+}
+
+impl Phase {
+    pub fn kind(&self) -> PhaseKind {
+        match self {
+            Phase::Seating => PhaseKind::Seating,
+            Phase::Dealing => PhaseKind::Dealing,
+            Phase::Bidding(_) => PhaseKind::Bidding,
+            Phase::Playing(_) => PhaseKind::Playing,
+            Phase::DealScored(_) => PhaseKind::DealScored,
+            Phase::MatchOver { .. } => PhaseKind::MatchOver,
+        }
+    }
+}
+
+// The phases without their data, including the lobby before there is a game
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct Game<'a> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PhaseKind {
+    Lobby,
+    Seating,
+    Dealing,
+    Bidding,
+    Playing,
+    DealScored,
+    MatchOver,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompletedRound {
+    play_round: PlayRound,
+    deal_score: DealScore,
+}
+
+impl CompletedRound {
+    pub fn play_round(&self) -> &PlayRound {
+        &self.play_round
+    }
+
+    pub fn deal_score(&self) -> DealScore {
+        self.deal_score
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::GameData"))]
+pub struct Game {
     settings: Settings,
-    table: Table<'a>,
+    table: Table,
+    score: Score,
+    dealer: Seat,
+    phase: Phase,
+    history: Vec<CompletedRound>,
+    spectators: Vec<Arc<User>>,
 }
 
-impl Game<'_> {
-    pub fn new(settings: Settings, table: Table) -> Game {
-        Game { settings, table }
+impl Game {
+    pub fn new(mut settings: Settings, table: Table) -> Game {
+        settings.seed.get_or_insert_with(rand::random);
+
+        Game {
+            settings,
+            score: Score::default(),
+            // The player who drew the highest card deals first
+            dealer: table.first_dealer(),
+            table,
+            phase: Phase::Seating,
+            history: Vec::new(),
+            spectators: Vec::new(),
+        }
+    }
+
+    pub fn table(&self) -> &Table {
+        &self.table
     }
 
-    pub fn start_round(&self) -> BidRound {
-        BidRound::new(0)
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    // The seat the user plays from, spectators don't have one
+    pub fn seat_of(&self, user: &User) -> Result<Seat, GameError> {
+        if self.is_spectator(user) {
+            return Err(GameError::Spectating);
+        }
+
+        self.table.seat_of(user).ok_or(GameError::NotInGame)
+    }
+
+    pub fn spectators(&self) -> &[Arc<User>] {
+        &self.spectators
+    }
+
+    pub fn add_spectator(&mut self, user: Arc<User>) -> Result<(), GameError> {
+        if self.is_spectator(&user) || self.table.seat_of(&user).is_some() {
+            return Err(GameError::AlreadyJoined);
+        }
+
+        self.spectators.push(user);
+
+        Ok(())
+    }
+
+    pub fn remove_spectator(&mut self, user: &User) -> Result<(), GameError> {
+        if !self.is_spectator(user) {
+            return Err(GameError::NotSpectating);
+        }
+
+        self.spectators.retain(|s| s.id() != user.id());
+
+        Ok(())
+    }
+
+    // Everything the player in the seat is allowed to know, clients and bots should only get this
+    pub fn player_view(&self, seat: Seat) -> PlayerView {
+        PlayerView::new(self, seat)
+    }
+
+    pub fn view_for(&self, user: &User) -> Result<PlayerView, GameError> {
+        Ok(self.player_view(self.seat_of(user)?))
+    }
+
+    pub fn spectator_view(&self) -> SpectatorView {
+        SpectatorView::new(self)
+    }
+
+    fn is_spectator(&self, user: &User) -> bool {
+        self.spectators.iter().any(|s| s.id() == user.id())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.settings.seed.expect("Game should always have a seed")
+    }
+
+    // Every deal gets its own seed derived from the match seed,
+    // so a single deal can be reproduced without replaying the ones before it
+    pub fn deal_seed(&self) -> u64 {
+        seed_for_deal(self.seed(), self.history.len() as u64 + 1)
+    }
+
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    pub fn phase(&self) -> &Phase {
+        &self.phase
+    }
+
+    // The seat whose action the game is waiting for, None once the match is over
+    pub fn awaited(&self) -> Option<Seat> {
+        match &self.phase {
+            Phase::Seating | Phase::Dealing | Phase::DealScored(_) => Some(self.dealer),
+            Phase::Bidding(bid_round) => bid_round.turn(),
+            Phase::Playing(play_round) => play_round.turn(),
+            Phase::MatchOver { .. } => None,
+        }
+    }
+
+    pub fn history(&self) -> &[CompletedRound] {
+        &self.history
+    }
+
+    // Leaves the seating or the scored deal behind, only the dealer can move on to the next deal
+    pub fn next_deal(&mut self, seat: Seat) -> Result<Seat, GameError> {
+        match self.phase {
+            Phase::Seating | Phase::Dealing | Phase::DealScored(_) => {
+                if seat != self.dealer {
+                    return Err(GameError::NotPlayersTurn);
+                }
+                self.phase = Phase::Dealing;
+
+                Ok(self.dealer)
+            }
+            Phase::Bidding(_) | Phase::Playing(_) => Err(GameError::RoundInProgress),
+            Phase::MatchOver { .. } => Err(GameError::MatchFinished),
+        }
+    }
+
+    pub fn start_round(&mut self, seat: Seat) -> Result<&BidRound, GameError> {
+        self.next_deal(seat)?;
+
+        let mut rng = StdRng::seed_from_u64(self.deal_seed());
+        let mut bid_round = BidRound::with_rng(self.dealer, &mut rng);
+        if !self.settings.nolo {
+            bid_round = bid_round.without_nolo();
+        }
+        self.phase = Phase::Bidding(bid_round);
+
+        match &self.phase {
+            Phase::Bidding(bid_round) => Ok(bid_round),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn register_bid(&mut self, seat: Seat, bid: Bid) -> Result<Option<Contract>, GameError> {
+        let Phase::Bidding(bid_round) = &mut self.phase else {
+            return Err(GameError::NotBidding);
+        };
+
+        let contract = bid_round.register_bid(seat, bid)?;
+
+        if contract.is_some() {
+            if let Phase::Bidding(bid_round) = std::mem::replace(&mut self.phase, Phase::Dealing) {
+                self.phase = Phase::Playing(bid_round.start_play()?);
+            }
+        }
+
+        Ok(contract)
+    }
+
+    // Returns the winner of the trick once the fourth card has been played
+    pub fn play_card(&mut self, player: Seat, card: Card) -> Result<Option<Seat>, GameError> {
+        let Phase::Playing(play_round) = &mut self.phase else {
+            return Err(GameError::NotPlaying);
+        };
+
+        let trick_winner = play_round.play_card(player, card)?;
+
+        if play_round.is_finished() {
+            self.finish_round()?;
+        }
+
+        Ok(trick_winner)
+    }
+
+    // Plays out the rest of the deal for a player who can't lose another trick
+    pub fn claim(&mut self, player: Seat) -> Result<Vec<(Seat, Card)>, GameError> {
+        let Phase::Playing(play_round) = &mut self.phase else {
+            return Err(GameError::NotPlaying);
+        };
+
+        let plays = play_round.claim(player)?;
+        self.finish_round()?;
+
+        Ok(plays)
+    }
+
+    // The player's team gives up the whole match. The deal in progress is abandoned
+    // without being scored or added to the history, the match over phase records the concession
+    pub fn concede(&mut self, player: Seat) -> Result<Team, GameError> {
+        if self.winner().is_some() {
+            return Err(GameError::MatchFinished);
+        }
+
+        let team = self.table.team_at(player);
+        self.phase = Phase::MatchOver { winner: team.opponent(), conceded: true };
+
+        Ok(team)
+    }
+
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    // The first team to reach the target score wins the match,
+    // unless the other team conceded before that
+    pub fn winner(&self) -> Option<Team> {
+        match self.phase {
+            Phase::MatchOver { winner, .. } => Some(winner),
+            _ => None,
+        }
+    }
+
+    // Whether the match ended by one team giving up rather than being played out
+    pub fn conceded(&self) -> bool {
+        matches!(self.phase, Phase::MatchOver { conceded: true, .. })
+    }
+
+    fn finish_round(&mut self) -> Result<DealScore, GameError> {
+        let Phase::Playing(play_round) = std::mem::replace(&mut self.phase, Phase::Dealing) else {
+            return Err(GameError::NotPlaying);
+        };
+
+        let deal_score = DealScore::new(&play_round, &self.table, self.settings.scoring)?;
+        self.score.add(deal_score);
+        self.history.push(CompletedRound { play_round, deal_score });
+
+        // The deal passes clockwise
+        self.dealer = self.dealer.clockwise();
+
+        self.phase = [Team::Lajvarna, Team::Gottarna].into_iter()
+            .find(|team| self.score.points(*team) >= self.settings.to_win as u16)
+            .map_or(Phase::DealScored(deal_score), |winner| Phase::MatchOver { winner, conceded: false });
+
+        Ok(deal_score)
+    }
+}
+
+// Mixes the match seed and the deal number, so that nearby match seeds
+// don't share deals the way plain seed + deal number would
+fn seed_for_deal(seed: u64, deal: u64) -> u64 {
+    splitmix(splitmix(seed) ^ deal)
+}
+
+// The SplitMix64 finalizer
+fn splitmix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Game::new always picks a seed, so a game read without one or with settings
+// a lobby would turn down is refused. The rounds and table check themselves
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{CompletedRound, Game, Phase, Settings};
+    use crate::errors::GameError;
+    use crate::game::score::Score;
+    use crate::game::seat::Seat;
+    use crate::game::table::Table;
+    use crate::user::User;
+    use std::sync::Arc;
+
+    #[derive(serde::Deserialize)]
+    pub(super) struct GameData {
+        settings: Settings,
+        table: Table,
+        score: Score,
+        dealer: Seat,
+        phase: Phase,
+        history: Vec<CompletedRound>,
+        spectators: Vec<Arc<User>>,
+    }
+
+    impl TryFrom<GameData> for Game {
+        type Error = GameError;
+
+        fn try_from(data: GameData) -> Result<Game, GameError> {
+            data.settings.validate()?;
+            if data.settings.seed.is_none() {
+                return Err(GameError::MissingSeed);
+            }
+
+            Ok(Game {
+                settings: data.settings,
+                table: data.table,
+                score: data.score,
+                dealer: data.dealer,
+                phase: data.phase,
+                history: data.history,
+                spectators: data.spectators,
+            })
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::game::bid_round::BidRound;
+    use crate::card::{Rank, Suit};
+    use crate::game::lobby::Lobby;
+    use crate::game::view::HandView;
     use super::*;
 
-    fn setup_users() -> Vec<User> {
+    fn setup_users() -> Vec<Arc<User>> {
         vec![
-            User::new("A"),
-            User::new("B"),
-            User::new("C"),
-            User::new("D"),
+            Arc::new(User::new("A")),
+            Arc::new(User::new("B")),
+            Arc::new(User::new("C")),
+            Arc::new(User::new("D")),
         ]
     }
 
-    fn setup_lobby() -> Lobby<'static> {
-        let settings = Settings { to_win: 13 };
+    fn setup_lobby() -> Lobby {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
         Lobby::new(settings)
     }
 
+    fn play_out_round(game: &mut Game) {
+        game.start_round(game.dealer()).unwrap();
+        let bidder = game.dealer().left_hand_opponent();
+        game.register_bid(bidder, Bid::Play).unwrap();
+
+        while let Phase::Playing(play_round) = game.phase() {
+            let seat = play_round.turn().unwrap();
+            let card = play_round.legal_cards(seat)[0];
+            game.play_card(seat, card).unwrap();
+        }
+    }
+
     #[test]
     fn new_game() {
-        let settings = Settings { to_win: 13 };
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
         let users = setup_users();
         let mut lobby = setup_lobby();
-        users.iter().for_each(|u| lobby.add_user(u));
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
         let table = Table::new(&lobby);
-        let game = Game::new(settings, table);
+        let game = Game::new(settings, table.clone());
 
-        assert_eq!(game, Game { settings, table });
+        assert!(game.settings.seed.is_some());
+        assert_eq!(
+            game,
+            Game {
+                settings: Settings { seed: game.settings.seed, ..settings },
+                table,
+                score: Score::default(),
+                dealer: Seat::North,
+                phase: Phase::Seating,
+                history: Vec::new(),
+                spectators: Vec::new(),
+            }
+        );
     }
 
     #[test]
     fn build_player() {
-        let user = User::new("John Doe");
-        let player = Player::build(&user, Team::Lajvarna);
+        let user = Arc::new(User::new("John Doe"));
+        let player = Player::build(user, Team::Lajvarna);
 
         assert_eq!(player.user().name(), "John Doe");
         assert_eq!(player.team(), Team::Lajvarna);
+        assert_eq!(player.seat(), None);
+    }
+
+    #[test]
+    fn spectators_join_and_leave_a_game() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+        let spectator = Arc::new(User::new("Kibitzer"));
+
+        assert_eq!(game.add_spectator(users[0].clone()), Err(GameError::AlreadyJoined));
+        assert_eq!(game.add_spectator(spectator.clone()), Ok(()));
+        assert_eq!(game.seat_of(&spectator), Err(GameError::Spectating));
+        assert_eq!(game.seat_of(&users[0]), Ok(game.table().seat_of(&users[0]).unwrap()));
+        assert_eq!(game.remove_spectator(&spectator), Ok(()));
+        assert_eq!(game.seat_of(&spectator), Err(GameError::NotInGame));
+    }
+
+    #[test]
+    fn spectators_never_see_hands_when_hidden() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+        game.register_bid(Seat::East, Bid::Play).unwrap();
+        let view = game.spectator_view();
+
+        assert!(view.hands.iter().all(|hand| matches!(hand, HandView::Hidden { cards: 13 })));
+        assert_eq!(view.contract, Some(Contract::Grand { bidder: Seat::East }));
+        assert_eq!(view.turn, Some(Seat::South));
+    }
+
+    #[test]
+    fn kibitzers_see_hands_with_a_delay() {
+        let settings = Settings { spectators: SpectatorMode::Kibitzer { delay: 1 }, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+        game.register_bid(Seat::East, Bid::Play).unwrap();
+        let Phase::Playing(play_round) = game.phase() else { unreachable!() };
+        let dealt_hands = play_round.hands().to_vec();
+
+        // Nothing is shown until the first trick lies a trick behind
+        assert!(game.spectator_view().hands.iter().all(|hand| matches!(hand, HandView::Hidden { .. })));
+
+        for _ in 0..5 {
+            let Phase::Playing(play_round) = game.phase() else { unreachable!() };
+            let seat = play_round.turn().unwrap();
+            let card = play_round.legal_cards(seat)[0];
+            game.play_card(seat, card).unwrap();
+        }
+        let view = game.spectator_view();
+
+        // One trick and a card played, the view shows the deal before any of it
+        assert!(view.tricks.is_empty());
+        assert_eq!(view.current_trick, None);
+        assert_eq!(view.turn, Some(Seat::South));
+        assert!(view.hands.iter().zip(dealt_hands).all(|(view, hand)| {
+            let HandView::Visible(shown) = view else { return false };
+            shown.len() == 13 && hand.cards().iter().all(|c| shown.contains(c))
+        }));
+    }
+
+    #[test]
+    fn delayed_kibitzers_see_the_end_of_a_scored_deal() {
+        let settings = Settings { to_win: 100, spectators: SpectatorMode::Kibitzer { delay: 2 }, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+        let dealer = game.dealer();
+
+        play_out_round(&mut game);
+        let view = game.spectator_view();
+
+        // Two tricks behind, the last two are still to be shown
+        assert_eq!(game.phase().kind(), PhaseKind::DealScored);
+        assert_eq!(view.dealer, dealer);
+        assert!(view.contract.is_some());
+        assert_eq!(view.tricks.len(), 11);
+        assert!(view.hands.iter().all(|hand| matches!(hand, HandView::Visible(shown) if shown.len() == 2)));
+
+        game.next_deal(game.dealer()).unwrap();
+        assert!(game.spectator_view().tricks.is_empty());
+    }
+
+    #[test]
+    fn player_view_only_shows_own_hand() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+        game.register_bid(Seat::East, Bid::Pass).unwrap();
+        let view = game.player_view(Seat::South);
+        let Phase::Bidding(bid_round) = game.phase() else { unreachable!() };
+
+        assert_eq!(view.hand(), Some(bid_round.hand(Seat::South)));
+        assert!(Seat::all().into_iter()
+            .filter(|seat| *seat != Seat::South)
+            .all(|seat| view.hands[seat.index()] == HandView::Hidden { cards: 13 }));
+        assert_eq!(view.bids, vec![(Seat::East, Bid::Pass)]);
+        assert_eq!(view.turn, Some(Seat::South));
+        assert!(view.legal_cards.is_empty());
+    }
+
+    #[test]
+    fn player_view_follows_the_play() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+        game.register_bid(Seat::East, Bid::Play).unwrap();
+        let Phase::Playing(play_round) = game.phase() else { unreachable!() };
+        let card = play_round.legal_cards(Seat::South)[0];
+        game.play_card(Seat::South, card).unwrap();
+
+        let view = game.player_view(Seat::West);
+
+        assert_eq!(view.contract, Some(Contract::Grand { bidder: Seat::East }));
+        assert_eq!(view.bids, vec![(Seat::East, Bid::Play)]);
+        assert_eq!(view.played_cards(), vec![card]);
+        assert_eq!(view.hands[Seat::South.index()], HandView::Hidden { cards: 12 });
+        assert_eq!(view.turn, Some(Seat::West));
+        assert!(!view.legal_cards.is_empty());
+        assert!(view.legal_cards.iter().all(|c| view.hand().unwrap().contains(c)));
+        assert!(game.player_view(Seat::North).legal_cards.is_empty());
+    }
+
+    #[test]
+    fn view_for_user() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let game = Game::new(Settings::default(), Table::new(&lobby));
+        let seat = game.table().seat_of(&users[2]).unwrap();
+
+        assert_eq!(game.view_for(&users[2]).map(|view| view.seat), Ok(seat));
+        assert_eq!(game.view_for(&User::new("Stranger")), Err(GameError::NotInGame));
+    }
+
+    #[test]
+    fn conceding_gives_the_match_to_the_opponents() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+        let team = game.table().team_at(Seat::East);
+
+        game.start_round(game.dealer()).unwrap();
+
+        assert_eq!(game.concede(Seat::East), Ok(team));
+        assert_eq!(game.winner(), Some(team.opponent()));
+        assert_eq!(game.phase(), &Phase::MatchOver { winner: team.opponent(), conceded: true });
+        assert!(game.conceded());
+        assert!(game.history().is_empty());
+        assert_eq!(game.concede(Seat::North), Err(GameError::MatchFinished));
+        assert_eq!(game.start_round(game.dealer()), Err(GameError::MatchFinished));
+    }
+
+    #[test]
+    fn claim_needs_a_round_in_play() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings::default(), Table::new(&lobby));
+
+        assert_eq!(game.claim(Seat::East), Err(GameError::NotPlaying));
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        assert_eq!(Settings::classic().validate(), Ok(()));
+        assert_eq!(Settings::short_game().validate(), Ok(()));
+        assert_eq!(Settings::default(), Settings::classic());
+    }
+
+    #[test]
+    fn validate_settings() {
+        let no_target = Settings { to_win: 0, ..Settings::default() };
+        let no_time = Settings { turn_time_limit: Some(Duration::ZERO), ..Settings::default() };
+        let thirty_seconds = Settings { turn_time_limit: Some(Duration::from_secs(30)), ..Settings::default() };
+
+        assert_eq!(no_target.validate(), Err(GameError::InvalidTarget));
+        assert_eq!(no_time.validate(), Err(GameError::InvalidTurnTimeLimit));
+        assert_eq!(thirty_seconds.validate(), Ok(()));
+    }
+
+    #[test]
+    fn dealer_must_play_without_nolo() {
+        let settings = Settings { nolo: false, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+        for seat in [Seat::East, Seat::South, Seat::West] {
+            game.register_bid(seat, Bid::Pass).unwrap();
+        }
+
+        assert_eq!(game.register_bid(Seat::North, Bid::Pass), Err(GameError::DealerMustPlay));
+        assert_eq!(
+            game.register_bid(Seat::North, Bid::Play),
+            Ok(Some(Contract::Grand { bidder: Seat::North })),
+        );
+    }
+
+    #[test]
+    fn team_of_chosen_seat() {
+        assert_eq!(Team::of_chosen_seat(Seat::North), Team::Lajvarna);
+        assert_eq!(Team::of_chosen_seat(Seat::East), Team::Gottarna);
+        assert_eq!(Team::of_chosen_seat(Seat::South), Team::Lajvarna);
+        assert_eq!(Team::of_chosen_seat(Seat::West), Team::Gottarna);
+    }
+
+    #[test]
+    fn draw_winner_deals_first_with_chosen_seats() {
+        let settings = Settings { to_win: 13, seed: None, seating: SeatingMode::Choose, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = Lobby::new(settings);
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        users.iter().zip(Seat::all()).for_each(|(u, seat)| lobby.choose_seat(u, seat).unwrap());
+        users.iter().for_each(|u| lobby.ready_up(u).unwrap());
+
+        let game = lobby.start_game().unwrap();
+        let draw_winner = game.table().dealer_draw().winner().user();
+
+        assert_eq!(game.table().seat_of(draw_winner), Some(game.dealer()));
+    }
+
+    #[test]
+    fn phases_follow_the_deal() {
+        let settings = Settings { to_win: 100, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        assert_eq!(game.phase().kind(), PhaseKind::Seating);
+        assert_eq!(game.awaited(), Some(Seat::North));
+        assert_eq!(game.next_deal(Seat::West), Err(GameError::NotPlayersTurn));
+        assert_eq!(game.start_round(Seat::East).err(), Some(GameError::NotPlayersTurn));
+        assert_eq!(game.next_deal(Seat::North), Ok(Seat::North));
+        assert_eq!(game.phase().kind(), PhaseKind::Dealing);
+
+        game.start_round(game.dealer()).unwrap();
+
+        assert_eq!(game.phase().kind(), PhaseKind::Bidding);
+        assert_eq!(game.awaited(), Some(Seat::East));
+        assert_eq!(game.next_deal(game.dealer()), Err(GameError::RoundInProgress));
+
+        game.register_bid(Seat::East, Bid::Play).unwrap();
+
+        assert_eq!(game.phase().kind(), PhaseKind::Playing);
+        assert_eq!(game.awaited(), Some(Seat::South));
+
+        while let Phase::Playing(play_round) = game.phase() {
+            let seat = play_round.turn().unwrap();
+            let card = play_round.legal_cards(seat)[0];
+            game.play_card(seat, card).unwrap();
+        }
+
+        // The deal has passed on to east
+        assert_eq!(game.phase().kind(), PhaseKind::DealScored);
+        assert_eq!(game.awaited(), Some(Seat::East));
+    }
+
+    #[test]
+    fn reaching_to_win_ends_the_match() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(Settings { to_win: 1, ..Settings::default() }, Table::new(&lobby));
+
+        play_out_round(&mut game);
+
+        assert_eq!(game.phase(), &Phase::MatchOver { winner: game.winner().unwrap(), conceded: false });
+        assert!(!game.conceded());
+        assert_eq!(game.awaited(), None);
+        assert_eq!(game.next_deal(game.dealer()), Err(GameError::MatchFinished));
+        assert_eq!(game.register_bid(Seat::East, Bid::Pass), Err(GameError::NotBidding));
     }
 
     #[test]
     fn start_round() {
-        let settings = Settings { to_win: 13 };
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
         let users = setup_users();
         let mut lobby = setup_lobby();
-        users.iter().for_each(|u| lobby.add_user(u));
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
         let table = Table::new(&lobby);
-        let game = Game::new(settings, table);
+        let mut game = Game::new(settings, table);
 
-        let bid_round = game.start_round();
+        let bid_round = game.start_round(game.dealer()).unwrap();
 
         assert_eq!(
             bid_round.bids().len(),
             0,
         );
+        assert_eq!(bid_round.dealer(), Seat::North);
+    }
+
+    #[test]
+    fn cant_start_round_while_one_is_in_progress() {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+
+        assert_eq!(game.start_round(game.dealer()), Err(GameError::RoundInProgress));
+    }
+
+    #[test]
+    fn resolved_bidding_moves_round_into_play() {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        game.start_round(game.dealer()).unwrap();
+
+        assert_eq!(game.register_bid(Seat::East, Bid::Pass), Ok(None));
+        assert_eq!(
+            game.play_card(Seat::East, Card::new(Suit::Spades, Rank::Ace)),
+            Err(GameError::NotPlaying),
+        );
+        assert_eq!(game.register_bid(Seat::West, Bid::Play), Err(GameError::NotPlayersTurn));
+        assert_eq!(
+            game.register_bid(Seat::South, Bid::Play),
+            Ok(Some(Contract::Grand { bidder: Seat::South })),
+        );
+        assert!(matches!(game.phase(), Phase::Playing(_)));
+        assert_eq!(game.register_bid(Seat::West, Bid::Pass), Err(GameError::NotBidding));
+    }
+
+    #[test]
+    fn finished_round_is_scored_and_kept_in_history() {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        play_out_round(&mut game);
+
+        let deal_score = game.history()[0].deal_score();
+        assert_eq!(game.phase(), &Phase::DealScored(deal_score));
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.score().points(deal_score.team()), deal_score.points());
+    }
+
+    #[test]
+    fn dealer_rotates_clockwise_after_every_deal() {
+        let settings = Settings { to_win: 100, seed: None, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        let dealers: Vec<Seat> = (0..5).map(|_| {
+            play_out_round(&mut game);
+            game.history().last().unwrap().play_round().dealer()
+        }).collect();
+
+        assert_eq!(
+            dealers,
+            vec![Seat::North, Seat::East, Seat::South, Seat::West, Seat::North],
+        );
+        assert_eq!(game.dealer(), Seat::East);
+    }
+
+    #[test]
+    fn match_is_played_until_a_team_reaches_to_win() {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        while game.winner().is_none() {
+            play_out_round(&mut game);
+        }
+
+        let winner = game.winner().unwrap();
+        assert!(game.score().points(winner) >= 13);
+        assert_eq!(game.start_round(game.dealer()), Err(GameError::MatchFinished));
+    }
+
+    #[test]
+    fn deal_seed_changes_every_deal() {
+        let settings = Settings { to_win: 100, seed: Some(1), ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let mut game = Game::new(settings, Table::new(&lobby));
+
+        let first = game.deal_seed();
+        play_out_round(&mut game);
+
+        assert_eq!(game.seed(), 1);
+        assert_ne!(game.deal_seed(), first);
+    }
+
+    #[test]
+    fn adjacent_match_seeds_share_no_deals() {
+        let deals = |seed: u64| -> Vec<BidRound> {
+            (1..=20)
+                .map(|deal| BidRound::with_rng(Seat::North, &mut StdRng::seed_from_u64(seed_for_deal(seed, deal))))
+                .collect()
+        };
+        let first = deals(41);
+        let second = deals(42);
+
+        assert!(first.iter().all(|deal| !second.contains(deal)));
+        assert!(first[1..].iter().all(|deal| *deal != first[0]));
+    }
+
+    #[test]
+    fn same_seed_reproduces_the_whole_match() {
+        let settings = Settings { to_win: 13, seed: Some(2024), ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = Lobby::new(settings);
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        users.iter().for_each(|u| lobby.ready_up(u).unwrap());
+
+        let mut game1 = lobby.clone().start_game().unwrap();
+        let mut game2 = lobby.clone().start_game().unwrap();
+        while game1.winner().is_none() {
+            play_out_round(&mut game1);
+            play_out_round(&mut game2);
+        }
+
+        assert_eq!(game1, game2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn game_survives_a_serde_round_trip() {
+        let settings = Settings { to_win: 30, seed: Some(3), ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = Lobby::new(settings);
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        users.iter().for_each(|u| lobby.ready_up(u).unwrap());
+        let mut game = lobby.start_game().unwrap();
+
+        play_out_round(&mut game);
+        game.start_round(game.dealer()).unwrap();
+        game.register_bid(game.dealer().left_hand_opponent(), Bid::Play).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let restored: Game = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, game);
+        assert!(json.contains("\"phase\":{\"playing\""));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reading_a_game_without_a_seed_is_an_error() {
+        let settings = Settings { seed: Some(3), ..Settings::default() };
+        let users = setup_users();
+        let mut lobby = Lobby::new(settings);
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        users.iter().for_each(|u| lobby.ready_up(u).unwrap());
+        let game = lobby.start_game().unwrap();
+        let json = serde_json::to_string(&game).unwrap();
+        let without_seed = json.replace("\"seed\":3", "\"seed\":null");
+
+        assert_ne!(without_seed, json);
+        assert!(serde_json::from_str::<Game>(&without_seed).unwrap_err().to_string().contains("seed"));
+    }
+
+    #[test]
+    fn game_can_be_moved_across_threads() {
+        fn assert_send_static<T: Send + 'static>() {}
+
+        assert_send_static::<Game>();
+        assert_send_static::<Lobby>();
     }
 }
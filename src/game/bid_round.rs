@@ -1,51 +1,175 @@
+use crate::deal;
 use crate::deck::Deck;
+use crate::errors::GameError;
+use crate::game::contract::Contract;
+use crate::game::play_round::PlayRound;
+use crate::game::seat::Seat;
 use crate::hand::Hand;
+use rand::Rng;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Bid {
     Pass,
     Play,
 }
 
-type WinningBid = Bid;
-
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::BidRoundData"))]
 pub struct BidRound {
     hands: Vec<Hand>,
-    dealer: usize,
-    bids: Vec<Bid>,
+    dealer: Seat,
+    bids: Vec<(Seat, Bid)>,
+    nolo: bool,
 }
 
 impl BidRound {
-    pub fn new(dealer: usize) -> BidRound {
+    pub fn new(dealer: Seat) -> BidRound {
+        Self::with_rng(dealer, &mut rand::thread_rng())
+    }
+
+    pub fn with_rng<R: Rng + ?Sized>(dealer: Seat, rng: &mut R) -> BidRound {
         let mut deck = Deck::new();
-        deck.shuffle();
-        let hands = deck.deal_hands();
+        deck.shuffle_with(rng);
+
+        Self::dealt(dealer, deck.deal_hands())
+    }
 
+    // Bidding on a given deal instead of a fresh shuffle, hands are indexed by seat
+    pub fn from_hands(dealer: Seat, hands: Vec<Hand>) -> Result<BidRound, GameError> {
+        deal::check(&hands)?;
+
+        Ok(Self::dealt(dealer, hands))
+    }
+
+    pub fn from_deal_index(dealer: Seat, index: u128) -> Result<BidRound, GameError> {
+        Ok(Self::dealt(dealer, deal::deal_from_index(index)?))
+    }
+
+    fn dealt(dealer: Seat, hands: Vec<Hand>) -> BidRound {
         BidRound {
             hands,
             dealer,
             bids: Vec::with_capacity(4),
+            nolo: true,
         }
     }
 
-    pub fn bids(&self) -> &[Bid] {
+    // Without nolo the dealer can't pass once everyone else has passed
+    pub fn without_nolo(mut self) -> BidRound {
+        self.nolo = false;
+        self
+    }
+
+    // Bids in the order they were made, together with the seat that made them
+    pub fn bids(&self) -> &[(Seat, Bid)] {
         &self.bids
     }
 
-    pub fn register_bid(&mut self, bid: Bid) -> Option<WinningBid> {
-        self.bids.push(bid);
+    pub fn bid_of(&self, seat: Seat) -> Option<Bid> {
+        self.bids.iter()
+            .find(|(s, _)| *s == seat)
+            .map(|(_, b)| *b)
+    }
+
+    pub fn hand(&self, seat: Seat) -> &Hand {
+        &self.hands[seat.index()]
+    }
 
-        if bid == Bid::Play {
-            return Some(Bid::Play);
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    // The number of the deal, see deal::deal_index
+    pub fn deal_index(&self) -> u128 {
+        deal::deal_index(&self.hands).expect("Bidding should always be on a whole deal")
+    }
+
+    // Bidding starts with the player to the left of the dealer
+    pub fn turn(&self) -> Option<Seat> {
+        if self.contract().is_some() {
+            return None;
+        }
+
+        Some(Seat::from_index(self.dealer.left_hand_opponent().index() + self.bids.len()))
+    }
+
+    pub fn register_bid(&mut self, seat: Seat, bid: Bid) -> Result<Option<Contract>, GameError> {
+        if self.contract().is_some() {
+            return Err(GameError::BiddingFinished);
+        }
+
+        if self.bid_of(seat).is_some() {
+            return Err(GameError::AlreadyBid);
+        }
+
+        if self.turn() != Some(seat) {
+            return Err(GameError::NotPlayersTurn);
+        }
+
+        if !self.nolo && bid == Bid::Pass && self.bids.len() == 3 {
+            return Err(GameError::DealerMustPlay);
+        }
+
+        self.bids.push((seat, bid));
+
+        Ok(self.contract())
+    }
+
+    pub fn contract(&self) -> Option<Contract> {
+        if let Some((bidder, _)) = self.bids.iter().find(|(_, b)| *b == Bid::Play) {
+            return Some(Contract::Grand { bidder: *bidder });
         }
 
         if self.bids.len() == 4 {
-            return Some(Bid::Pass);
+            return Some(Contract::Nolo);
         }
 
         None
     }
+
+    pub fn start_play(self) -> Result<PlayRound, GameError> {
+        let contract = self.contract()
+            .ok_or(GameError::BiddingNotFinished)?;
+
+        Ok(PlayRound::new(self.hands, self.dealer, contract).with_bids(self.bids))
+    }
+}
+
+// Read through from_hands and register_bid, so only whole deals and legal bidding get in
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{Bid, BidRound};
+    use crate::errors::GameError;
+    use crate::game::seat::Seat;
+    use crate::hand::Hand;
+
+    #[derive(serde::Deserialize)]
+    pub(super) struct BidRoundData {
+        hands: Vec<Hand>,
+        dealer: Seat,
+        bids: Vec<(Seat, Bid)>,
+        nolo: bool,
+    }
+
+    impl TryFrom<BidRoundData> for BidRound {
+        type Error = GameError;
+
+        fn try_from(data: BidRoundData) -> Result<BidRound, GameError> {
+            let mut bid_round = BidRound::from_hands(data.dealer, data.hands)?;
+            if !data.nolo {
+                bid_round = bid_round.without_nolo();
+            }
+
+            for (seat, bid) in data.bids {
+                bid_round.register_bid(seat, bid)?;
+            }
+
+            Ok(bid_round)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -54,36 +178,165 @@ mod tests {
 
     #[test]
     fn new_bid_round() {
-        let bid_round = BidRound::new(0);
+        let bid_round = BidRound::new(Seat::North);
 
         assert_eq!(bid_round.hands.len(), 4);
-        assert_eq!(bid_round.dealer, 0);
+        assert_eq!(bid_round.dealer, Seat::North);
         assert_eq!(bid_round.bids().len(), 0);
     }
 
+    #[test]
+    fn same_seed_deals_same_hands() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let bid_round1 = BidRound::with_rng(Seat::North, &mut StdRng::seed_from_u64(3));
+        let bid_round2 = BidRound::with_rng(Seat::North, &mut StdRng::seed_from_u64(3));
+
+        assert_eq!(bid_round1.hands, bid_round2.hands);
+    }
+
     #[test]
     fn register_a_play_bid_triggers_play() {
-        let mut bid_round = BidRound::new(0);
+        let mut bid_round = BidRound::new(Seat::North);
+
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Play), Ok(Some(Contract::Grand { bidder: Seat::East })));
+    }
+
+    #[test]
+    fn grand_records_who_said_play() {
+        let mut bid_round = BidRound::new(Seat::South);
 
-        assert_eq!(bid_round.register_bid(Bid::Play), Some(Bid::Play));
+        assert_eq!(bid_round.register_bid(Seat::West, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::North, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Play), Ok(Some(Contract::Grand { bidder: Seat::East })));
     }
 
     #[test]
     fn register_four_pass_bids_triggers_pass() {
-        let mut bid_round = BidRound::new(0);
+        let mut bid_round = BidRound::new(Seat::North);
 
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
-        assert_eq!(bid_round.register_bid(Bid::Pass), Some(Bid::Pass));
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::South, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::West, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::North, Bid::Pass), Ok(Some(Contract::Nolo)));
     }
 
     #[test]
     fn register_three_pass_bids_does_not_trigger_pass() {
-        let mut bid_round = BidRound::new(0);
+        let mut bid_round = BidRound::new(Seat::North);
+
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::South, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::West, Bid::Pass), Ok(None));
+    }
+
+    #[test]
+    fn bidding_starts_left_of_dealer() {
+        let mut bid_round = BidRound::new(Seat::West);
+
+        assert_eq!(bid_round.turn(), Some(Seat::North));
+        assert_eq!(bid_round.register_bid(Seat::West, Bid::Pass), Err(GameError::NotPlayersTurn));
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Pass), Err(GameError::NotPlayersTurn));
+        assert_eq!(bid_round.register_bid(Seat::North, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.turn(), Some(Seat::East));
+    }
+
+    #[test]
+    fn denies_bidding_twice() {
+        let mut bid_round = BidRound::new(Seat::North);
+
+        bid_round.register_bid(Seat::East, Bid::Pass).unwrap();
+
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Pass), Err(GameError::AlreadyBid));
+        assert_eq!(bid_round.bid_of(Seat::East), Some(Bid::Pass));
+        assert_eq!(bid_round.bid_of(Seat::South), None);
+    }
+
+    #[test]
+    fn denies_bidding_after_resolution() {
+        let mut bid_round = BidRound::new(Seat::North);
+
+        bid_round.register_bid(Seat::East, Bid::Play).unwrap();
+
+        assert_eq!(bid_round.turn(), None);
+        assert_eq!(bid_round.register_bid(Seat::South, Bid::Pass), Err(GameError::BiddingFinished));
+        assert_eq!(bid_round.bids(), &[(Seat::East, Bid::Play)]);
+    }
+
+    #[test]
+    fn cant_start_play_before_bidding_is_finished() {
+        let mut bid_round = BidRound::new(Seat::North);
+        bid_round.register_bid(Seat::East, Bid::Pass).unwrap();
+
+        assert_eq!(bid_round.start_play(), Err(GameError::BiddingNotFinished));
+    }
+
+    #[test]
+    fn start_nolo_play_after_four_passes() {
+        let mut bid_round = BidRound::new(Seat::West);
+        Seat::all().into_iter().for_each(|seat| {
+            bid_round.register_bid(seat, Bid::Pass).unwrap();
+        });
+
+        let play_round = bid_round.start_play().unwrap();
+
+        assert_eq!(play_round.contract(), Contract::Nolo);
+        assert_eq!(play_round.leader(), Seat::North);
+    }
+
+    #[test]
+    fn start_play_after_play_bid() {
+        let mut bid_round = BidRound::new(Seat::North);
+        bid_round.register_bid(Seat::East, Bid::Pass).unwrap();
+        bid_round.register_bid(Seat::South, Bid::Play).unwrap();
+
+        let play_round = bid_round.start_play().unwrap();
+
+        assert_eq!(play_round.contract(), Contract::Grand { bidder: Seat::South });
+        assert_eq!(play_round.bids(), &[(Seat::East, Bid::Pass), (Seat::South, Bid::Play)]);
+        assert_eq!(play_round.leader(), Seat::West);
+        assert_eq!(play_round.hands().len(), 4);
+        assert_eq!(play_round.tricks().len(), 0);
+    }
+
+    #[test]
+    fn dealer_cant_pass_without_nolo() {
+        let mut bid_round = BidRound::new(Seat::North).without_nolo();
+
+        assert_eq!(bid_round.register_bid(Seat::East, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::South, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::West, Bid::Pass), Ok(None));
+        assert_eq!(bid_round.register_bid(Seat::North, Bid::Pass), Err(GameError::DealerMustPlay));
+        assert_eq!(bid_round.register_bid(Seat::North, Bid::Play), Ok(Some(Contract::Grand { bidder: Seat::North })));
+    }
+
+    #[test]
+    fn bidding_on_a_given_deal() {
+        let mut deck = Deck::new();
+        deck.shuffle();
+        let hands = deck.deal_hands();
+
+        let bid_round = BidRound::from_hands(Seat::East, hands.clone()).unwrap();
+        let index = bid_round.deal_index();
+
+        assert_eq!(bid_round.dealer(), Seat::East);
+        assert_eq!(bid_round.hand(Seat::North), &hands[0]);
+        assert_eq!(BidRound::from_deal_index(Seat::East, index), Ok(bid_round));
+        assert_eq!(BidRound::from_hands(Seat::East, hands[..3].to_vec()), Err(GameError::InvalidDeal));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reading_a_broken_bid_round_is_an_error() {
+        let mut bid_round = BidRound::new(Seat::North);
+        bid_round.register_bid(Seat::East, Bid::Pass).unwrap();
+        let json = serde_json::to_string(&bid_round).unwrap();
+        let out_of_turn = json.replace(r#"[["east","pass"]]"#, r#"[["south","pass"]]"#);
 
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
-        assert_eq!(bid_round.register_bid(Bid::Pass), None);
+        assert_eq!(serde_json::from_str::<BidRound>(&json).unwrap(), bid_round);
+        assert_ne!(out_of_turn, json);
+        assert!(serde_json::from_str::<BidRound>(&out_of_turn).is_err());
+        assert!(serde_json::from_str::<BidRound>(r#"{"hands":[],"dealer":"north","bids":[],"nolo":true}"#).is_err());
     }
 }
@@ -0,0 +1,77 @@
+use crate::game::seat::Seat;
+use crate::game::table::Table;
+use crate::game::Team;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Contract {
+    // Someone said play, the side taking the most tricks wins
+    Grand { bidder: Seat },
+    // Everyone passed, the side taking the fewest tricks wins
+    Nolo,
+}
+
+impl Contract {
+    pub fn bidder(&self) -> Option<Seat> {
+        match self {
+            Contract::Grand { bidder } => Some(*bidder),
+            Contract::Nolo => None,
+        }
+    }
+
+    pub fn is_bidding_side(&self, seat: Seat) -> bool {
+        match self {
+            Contract::Grand { bidder } => bidder.same_side(seat),
+            Contract::Nolo => false,
+        }
+    }
+
+    pub fn bidding_team(&self, table: &Table) -> Option<Team> {
+        self.bidder().map(|bidder| table.team_at(bidder))
+    }
+
+    // Grand is led by the player to the left of the bidder,
+    // nolo by the player to the left of the dealer
+    pub fn opening_leader(&self, dealer: Seat) -> Seat {
+        match self {
+            Contract::Grand { bidder } => bidder.left_hand_opponent(),
+            Contract::Nolo => dealer.left_hand_opponent(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grand_records_bidder() {
+        let contract = Contract::Grand { bidder: Seat::South };
+
+        assert_eq!(contract.bidder(), Some(Seat::South));
+        assert_eq!(Contract::Nolo.bidder(), None);
+    }
+
+    #[test]
+    fn bidding_side_includes_partner() {
+        let contract = Contract::Grand { bidder: Seat::East };
+
+        assert!(contract.is_bidding_side(Seat::East));
+        assert!(contract.is_bidding_side(Seat::West));
+        assert!(!contract.is_bidding_side(Seat::North));
+        assert!(!contract.is_bidding_side(Seat::South));
+    }
+
+    #[test]
+    fn nolo_has_no_bidding_side() {
+        assert!(Seat::all().into_iter().all(|seat| !Contract::Nolo.is_bidding_side(seat)));
+    }
+
+    #[test]
+    fn opening_leader() {
+        assert_eq!(Contract::Grand { bidder: Seat::West }.opening_leader(Seat::North), Seat::North);
+        assert_eq!(Contract::Grand { bidder: Seat::East }.opening_leader(Seat::North), Seat::South);
+        assert_eq!(Contract::Nolo.opening_leader(Seat::West), Seat::North);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::errors::GameError;
+use crate::game::session::{GameCommand, GameEvent, Session};
+use crate::game::Settings;
+use crate::user::User;
+use std::sync::Arc;
+
+// Every event of a session in the order it happened, enough to rebuild the session from scratch
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventLog {
+    settings: Settings,
+    events: Vec<GameEvent>,
+}
+
+impl EventLog {
+    pub fn new(settings: Settings) -> EventLog {
+        EventLog {
+            settings,
+            events: Vec::new(),
+        }
+    }
+
+    // The settings the session was created with
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn record(&mut self, events: &[GameEvent]) {
+        self.events.extend_from_slice(events);
+    }
+
+    // Applies the command and records whatever it led to
+    pub fn apply(&mut self, session: &mut Session, user: &Arc<User>, command: GameCommand) -> Result<Vec<GameEvent>, GameError> {
+        let events = session.apply(user, command)?;
+        self.record(&events);
+
+        Ok(events)
+    }
+
+    pub fn replay(&self) -> Result<Session, GameError> {
+        self.replay_until(self.events.len())
+    }
+
+    // The session as it stood after the given number of events
+    pub fn replay_until(&self, position: usize) -> Result<Session, GameError> {
+        Session::from_events(self.settings, &self.events[..position.min(self.events.len())])
+    }
+
+    // Where every deal starts in the log, replaying until there shows the table right before the deal
+    pub fn deal_positions(&self) -> Vec<usize> {
+        self.events.iter()
+            .enumerate()
+            .filter(|(_, event)| matches!(event, GameEvent::Dealt { .. }))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::bid_round::Bid;
+    use crate::game::seat::Seat;
+    use crate::game::{Phase, PhaseKind};
+
+    fn setup_users() -> Vec<Arc<User>> {
+        vec![
+            Arc::new(User::new("A")),
+            Arc::new(User::new("B")),
+            Arc::new(User::new("C")),
+            Arc::new(User::new("D")),
+        ]
+    }
+
+    fn user_at(session: &Session, users: &[Arc<User>], seat: Seat) -> Arc<User> {
+        let game = session.game().unwrap();
+
+        users.iter()
+            .find(|u| game.table().seat_of(u) == Some(seat))
+            .unwrap()
+            .clone()
+    }
+
+    // Plays a whole match through the log, the dealer's left-hand opponent always says play
+    fn play_match(users: &[Arc<User>], settings: Settings) -> (Session, EventLog) {
+        let mut session = Session::new(settings);
+        let mut log = EventLog::new(settings);
+        let spectator = Arc::new(User::new("Kibitzer"));
+
+        users.iter().for_each(|u| { log.apply(&mut session, u, GameCommand::Join).unwrap(); });
+        log.apply(&mut session, &spectator, GameCommand::Spectate).unwrap();
+        log.apply(&mut session, &users[0], GameCommand::Lock).unwrap();
+        users.iter().for_each(|u| { log.apply(&mut session, u, GameCommand::Ready).unwrap(); });
+        log.apply(&mut session, &users[0], GameCommand::Start).unwrap();
+
+        while let Some(seat) = session.awaited() {
+            let game = session.game().unwrap();
+            let command = match game.phase() {
+                Phase::Bidding(_) => GameCommand::Bid(Bid::Play),
+                Phase::Playing(play_round) => GameCommand::PlayCard(play_round.legal_cards(seat)[0]),
+                _ => GameCommand::Deal,
+            };
+            let user = user_at(&session, users, seat);
+            log.apply(&mut session, &user, command).unwrap();
+        }
+
+        (session, log)
+    }
+
+    #[test]
+    fn replaying_the_log_rebuilds_the_match() {
+        let users = setup_users();
+        let (session, log) = play_match(&users, Settings { to_win: 5, ..Settings::default() });
+
+        assert_eq!(session.phase(), PhaseKind::MatchOver);
+        assert_eq!(log.replay(), Ok(session));
+    }
+
+    #[test]
+    fn replaying_part_of_the_log_rebuilds_a_deal() {
+        let users = setup_users();
+        let (session, log) = play_match(&users, Settings { to_win: 30, ..Settings::default() });
+        let positions = log.deal_positions();
+        let game = session.game().unwrap();
+
+        assert_eq!(positions.len(), game.history().len());
+
+        let before_second_deal = log.replay_until(positions[1]).unwrap();
+
+        assert_eq!(before_second_deal.phase(), PhaseKind::DealScored);
+        assert_eq!(before_second_deal.game().unwrap().history(), &game.history()[..1]);
+    }
+
+    #[test]
+    fn tampered_log_is_refused() {
+        let users = setup_users();
+        let (_, log) = play_match(&users, Settings { to_win: 5, ..Settings::default() });
+        let mut tampered = EventLog::new(*log.settings());
+        let events: Vec<GameEvent> = log.events().iter()
+            .map(|event| match event {
+                GameEvent::Dealt { dealer, seed, hands } => GameEvent::Dealt {
+                    dealer: *dealer,
+                    seed: *seed,
+                    hands: hands.iter().rev().cloned().collect(),
+                },
+                event => event.clone(),
+            })
+            .collect();
+        tampered.record(&events);
+
+        assert_eq!(tampered.replay(), Err(GameError::ReplayMismatch));
+    }
+
+    #[test]
+    fn replayed_concession_is_told_apart_from_a_played_match() {
+        let users = setup_users();
+        let settings = Settings::default();
+        let mut session = Session::new(settings);
+        let mut log = EventLog::new(settings);
+
+        users.iter().for_each(|u| { log.apply(&mut session, u, GameCommand::Join).unwrap(); });
+        users.iter().for_each(|u| { log.apply(&mut session, u, GameCommand::Ready).unwrap(); });
+        log.apply(&mut session, &users[0], GameCommand::Start).unwrap();
+        let dealer = user_at(&session, &users, session.awaited().unwrap());
+        log.apply(&mut session, &dealer, GameCommand::Deal).unwrap();
+        log.apply(&mut session, &users[0], GameCommand::Concede).unwrap();
+
+        let replayed = log.replay().unwrap();
+        let game = replayed.game().unwrap();
+
+        assert!(game.conceded());
+        assert!(game.history().is_empty());
+        assert_eq!(replayed, session);
+    }
+}
@@ -1,74 +1,286 @@
 use crate::errors::GameError;
-use crate::game::{Game, Player, Settings, Team};
-use crate::user::User;
+use crate::game::{Game, Player, SeatingMode, Settings, Team};
+use crate::game::seat::Seat;
+use crate::user::{User, UserId};
 use crate::game::table::Table;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Arc;
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct Lobby<'a> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lobby {
     pub(crate) settings: Settings,
-    pub(crate) players: Vec<Player<'a>>,
+    pub(crate) players: Vec<Player>,
+    spectators: Vec<Arc<User>>,
+    host: Option<UserId>,
+    locked: bool,
+    started: bool,
 }
 
-impl<'a> Lobby<'a> {
-    pub fn new(settings: Settings) -> Lobby<'a> {
+impl Lobby {
+    pub fn new(settings: Settings) -> Lobby {
         Lobby {
             settings,
             players: Vec::new(),
+            spectators: Vec::new(),
+            host: None,
+            locked: false,
+            started: false,
         }
     }
 
-    pub fn add_user(&mut self, user: &'a User) {
-        // Create player
-        let player = Player::build(
-            &user,
-            self.team_to_assign_to()
-        );
+    pub fn add_user(&mut self, user: Arc<User>) -> Result<(), GameError> {
+        self.ensure_not_started()?;
 
         // Deny player if already in the game
-        if self.players.iter()
-            .any(|p| &p.user() == &user) {
-            return;
+        if self.is_member(&user) {
+            return Err(GameError::AlreadyJoined);
+        }
+
+        if self.locked {
+            return Err(GameError::LobbyLocked);
         }
 
         // Deny player if there are already 4 players
         if self.players.len() == 4 {
-            return;
+            return Err(GameError::LobbyFull);
         }
 
+        // The first player to join hosts the lobby
+        self.host.get_or_insert(user.id());
+
+        // Create player
+        let player = Player::build(
+            user,
+            self.team_to_assign_to()
+        );
+
         self.players.push(player);
+
+        Ok(())
+    }
+
+    // Spectators don't take a seat, so any number of them can follow along
+    pub fn add_spectator(&mut self, user: Arc<User>) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+
+        if self.is_member(&user) {
+            return Err(GameError::AlreadyJoined);
+        }
+
+        if self.locked {
+            return Err(GameError::LobbyLocked);
+        }
+
+        self.spectators.push(user);
+
+        Ok(())
     }
 
-    fn del_user(&mut self, user: &'a User) {
-        self.players.retain(|p| &p.user() != &user);
+    pub fn del_spectator(&mut self, user: &User) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+
+        if !self.spectators.iter().any(|s| s.id() == user.id()) {
+            return Err(GameError::NotSpectating);
+        }
+
+        self.spectators.retain(|s| s.id() != user.id());
+
+        Ok(())
     }
 
-    fn change_team(&mut self, user: &'a User, team: Team) {
-        if let Some(player) = self.players
-            .iter_mut()
-            .find(|p| p.user() == user) {
+    pub fn spectators(&self) -> &[Arc<User>] {
+        &self.spectators
+    }
+
+    pub fn del_user(&mut self, user: &User) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+        self.player_mut(user)?;
+
+        self.players.retain(|p| p.user().id() != user.id());
+
+        // The longest seated player takes over as host
+        if self.host == Some(user.id()) {
+            self.host = self.players.first().map(|p| p.user().id());
+        }
+
+        // The remaining players have to confirm the new line-up
+        self.unready_all();
+
+        Ok(())
+    }
+
+    pub fn kick(&mut self, host: &User, user: &User) -> Result<(), GameError> {
+        self.ensure_host(host)?;
+
+        self.del_user(user)
+    }
+
+    pub fn transfer_host(&mut self, host: &User, user: &User) -> Result<(), GameError> {
+        self.ensure_host(host)?;
+        self.player_mut(user)?;
+
+        self.host = Some(user.id());
+
+        Ok(())
+    }
+
+    pub fn lock(&mut self, host: &User) -> Result<(), GameError> {
+        self.ensure_host(host)?;
+        self.locked = true;
+
+        Ok(())
+    }
+
+    pub fn unlock(&mut self, host: &User) -> Result<(), GameError> {
+        self.ensure_host(host)?;
+        self.locked = false;
+
+        Ok(())
+    }
+
+    pub fn update_settings(&mut self, host: &User, settings: Settings) -> Result<(), GameError> {
+        self.ensure_host(host)?;
+        settings.validate()?;
+
+        // Seats chosen under one mode mean nothing under another
+        if settings.seating != self.settings.seating {
+            self.players.iter_mut().for_each(|p| p.seat = None);
+        }
+
+        self.settings = settings;
+        // Everyone has to agree to the new rules
+        self.unready_all();
+
+        Ok(())
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn host(&self) -> Option<&Player> {
+        self.players.iter()
+            .find(|p| Some(p.user().id()) == self.host)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn change_team(&mut self, user: &User, team: Team) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+
+        // With chosen seats the team follows from the seat
+        if self.settings.seating == SeatingMode::Choose {
+            return Err(GameError::WrongSeatingMode);
+        }
+
+        let team_count = self.players.iter()
+            .filter(|p| p.team == team && p.user().id() != user.id())
+            .count();
+        let player = self.player_mut(user)?;
+
+        if player.team != team && team_count >= 2 {
+            return Err(GameError::TeamFull);
+        }
+
+        if player.team != team {
             player.team = team;
+            // Both teams changed, so everyone has to ready up again
+            self.unready_all();
         }
+
+        Ok(())
     }
 
-    pub fn ready_up(&mut self, user: &'a User) {
-        if let Some(player) = self.players
-            .iter_mut()
-            .find(|p| p.user() == user) {
-            player.ready = true;
+    pub fn choose_seat(&mut self, user: &User, seat: Seat) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+
+        if self.settings.seating != SeatingMode::Choose {
+            return Err(GameError::WrongSeatingMode);
         }
+
+        if self.players.iter()
+            .any(|p| p.seat == Some(seat) && p.user().id() != user.id()) {
+            return Err(GameError::SeatTaken);
+        }
+
+        let player = self.player_mut(user)?;
+
+        if player.seat != Some(seat) {
+            player.seat = Some(seat);
+            player.team = Team::of_chosen_seat(seat);
+            self.unready_all();
+        }
+
+        Ok(())
     }
 
-    fn unready(&mut self, user: &'a User) {
-        if let Some(player) = self.players
-            .iter_mut()
-            .find(|p| p.user() == user) {
-            player.ready = false;
+    pub fn ready_up(&mut self, user: &User) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+        self.player_mut(user)?.ready = true;
+
+        Ok(())
+    }
+
+    pub fn unready(&mut self, user: &User) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+        self.player_mut(user)?.ready = false;
+
+        Ok(())
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    fn unready_all(&mut self) {
+        self.players.iter_mut().for_each(|p| p.ready = false);
+    }
+
+    fn ensure_host(&self, user: &User) -> Result<(), GameError> {
+        self.ensure_not_started()?;
+
+        if self.host != Some(user.id()) {
+            return Err(GameError::NotHost);
         }
+
+        Ok(())
+    }
+
+    fn ensure_not_started(&self) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        Ok(())
+    }
+
+    fn is_member(&self, user: &User) -> bool {
+        self.players.iter().any(|p| p.user().id() == user.id())
+            || self.spectators.iter().any(|s| s.id() == user.id())
+    }
+
+    fn player_mut(&mut self, user: &User) -> Result<&mut Player, GameError> {
+        if self.spectators.iter().any(|s| s.id() == user.id()) {
+            return Err(GameError::Spectating);
+        }
+
+        self.players
+            .iter_mut()
+            .find(|p| p.user().id() == user.id())
+            .ok_or(GameError::NotInLobby)
     }
 
     fn ready_count(&self) -> usize {
         self.players.iter()
-            .filter(|p| p.ready == true)
+            .filter(|p| p.ready)
             .count()
     }
 
@@ -97,23 +309,34 @@ impl<'a> Lobby<'a> {
         }
     }
 
-    pub fn start_game(&self) -> Result<Game, GameError> {
+    pub fn start_game(&mut self) -> Result<Game, GameError> {
+        self.ensure_not_started()?;
+
         if self.ready_count() != 4 {
             return Err(GameError::RequiresFourReadyPlayers);
         }
 
+        if self.settings.seating == SeatingMode::Choose
+            && self.players.iter().any(|p| p.seat.is_none()) {
+            return Err(GameError::SeatsNotChosen);
+        }
+
         if !self.balanced_teams() {
             return Err(GameError::UnbalancedTeams);
         }
 
-        let table = Table::new(self);
+        // The seat draw uses the match seed so the whole match can be replayed
+        let seed = self.settings.seed.unwrap_or_else(rand::random);
+        let table = Table::with_rng(self, &mut StdRng::seed_from_u64(seed));
+        self.started = true;
+
+        let mut game = Game::new(
+            Settings { seed: Some(seed), ..self.settings },
+            table,
+        );
+        game.spectators = self.spectators.clone();
 
-        Ok(
-            Game::new(
-                self.settings,
-                table,
-            )
-        )
+        Ok(game)
     }
 }
 
@@ -121,20 +344,20 @@ impl<'a> Lobby<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::game::{Settings, Team};
+    use crate::game::{SeatingMode, Settings, Team};
     use super::*;
 
-    fn setup_lobby() -> Lobby<'static> {
-        let settings = Settings { to_win: 13 };
+    fn setup_lobby() -> Lobby {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
         Lobby::new(settings)
     }
 
     #[test]
     fn accepts_user_joining() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
+        let user = Arc::new(User::new("John Doe"));
 
-        game_lobby.add_user(&user);
+        game_lobby.add_user(user.clone()).unwrap();
 
         assert_eq!(game_lobby.players.len(), 1);
     }
@@ -142,23 +365,23 @@ mod tests {
     #[test]
     fn denies_player_joining_twice() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
+        let user = Arc::new(User::new("John Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user);
+        game_lobby.add_user(user.clone()).unwrap();
 
+        assert_eq!(game_lobby.add_user(user.clone()), Err(GameError::AlreadyJoined));
         assert_eq!(game_lobby.players.len(), 1);
     }
 
     #[test]
     fn accepts_user_leaving() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.del_user(&user);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.del_user(&user).unwrap();
 
         assert_eq!(game_lobby.players.len(), 1);
     }
@@ -167,75 +390,74 @@ mod tests {
     #[test]
     fn denies_user_leaving_twice() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.del_user(&user);
-        game_lobby.del_user(&user);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.del_user(&user).unwrap();
 
+        assert_eq!(game_lobby.del_user(&user), Err(GameError::NotInLobby));
         assert_eq!(game_lobby.players.len(), 1);
     }
 
     #[test]
     fn assigning_users_evenly_between_teams() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
-        let user3 = User::new("Dolly");
-        let user4 = User::new("Phil");
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+        let user4 = Arc::new(User::new("Phil"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.add_user(&user3);
-        game_lobby.add_user(&user4);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.add_user(user4.clone()).unwrap();
 
-        assert_eq!(game_lobby.balanced_teams(), true);
+        assert!(game_lobby.balanced_teams());
     }
 
     #[test]
     fn denies_5th_player() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
-        let user3 = User::new("Dolly");
-        let user4 = User::new("Phil");
-        let user5 = User::new("John Johnson");
-
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.add_user(&user3);
-        game_lobby.add_user(&user4);
-        game_lobby.add_user(&user5);
-
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+        let user4 = Arc::new(User::new("Phil"));
+        let user5 = Arc::new(User::new("John Johnson"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.add_user(user4.clone()).unwrap();
+
+        assert_eq!(game_lobby.add_user(user5.clone()), Err(GameError::LobbyFull));
         assert_eq!(game_lobby.players.len(), 4);
-        assert_eq!(game_lobby.players.iter().any(|p| p.user == &user5), false);
+        assert!(!game_lobby.players.iter().any(|p| p.user.id() == user5.id()));
     }
 
 
     #[test]
     fn allowing_players_to_change_team() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
+        let user = Arc::new(User::new("John Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.change_team(&user, Team::Gottarna);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.change_team(&user, Team::Gottarna).unwrap();
 
-        assert_eq!(
+        assert!(
             game_lobby.players.iter()
-                .any(|p| p.user == &user && p.team == Team::Gottarna),
-            true
+                .any(|p| p.user.id() == user.id() && p.team == Team::Gottarna)
         );
     }
 
     #[test]
     fn player_can_ready_up() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
+        let user = Arc::new(User::new("John Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.ready_up(&user);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
 
         assert_eq!(game_lobby.ready_count(), 1);
     }
@@ -243,11 +465,11 @@ mod tests {
     #[test]
     fn readying_up_twice_doesnt_change_anything() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
+        let user = Arc::new(User::new("John Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.ready_up(&user);
-        game_lobby.ready_up(&user);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user).unwrap();
 
         assert_eq!(game_lobby.ready_count(), 1);
     }
@@ -255,14 +477,14 @@ mod tests {
     #[test]
     fn player_can_unready() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.ready_up(&user);
-        game_lobby.ready_up(&user2);
-        game_lobby.unready(&user);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.unready(&user).unwrap();
 
         assert_eq!(game_lobby.ready_count(), 1);
     }
@@ -270,16 +492,16 @@ mod tests {
     #[test]
     fn starting_requires_four_ready_players() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
-        let user3 = User::new("Dolly");
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
 
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.add_user(&user3);
-        game_lobby.ready_up(&user);
-        game_lobby.ready_up(&user2);
-        game_lobby.ready_up(&user3);
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.ready_up(&user3).unwrap();
 
         assert_eq!(game_lobby.start_game(), Err(GameError::RequiresFourReadyPlayers));
     }
@@ -287,23 +509,21 @@ mod tests {
     #[test]
     fn cant_start_with_unbalanced_teams() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
-        let user3 = User::new("Dolly");
-        let user4 = User::new("Homer");
-
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.add_user(&user3);
-        game_lobby.add_user(&user4);
-        game_lobby.change_team(&user, Team::Lajvarna);
-        game_lobby.change_team(&user2, Team::Gottarna);
-        game_lobby.change_team(&user3, Team::Lajvarna);
-        game_lobby.change_team(&user4, Team::Lajvarna);
-        game_lobby.ready_up(&user);
-        game_lobby.ready_up(&user2);
-        game_lobby.ready_up(&user3);
-        game_lobby.ready_up(&user4);
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+        let user4 = Arc::new(User::new("Homer"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.add_user(user4.clone()).unwrap();
+        // Changing team can't unbalance a full lobby, so force it
+        game_lobby.players[3].team = Team::Lajvarna;
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.ready_up(&user3).unwrap();
+        game_lobby.ready_up(&user4).unwrap();
 
         assert_eq!(game_lobby.start_game(), Err(GameError::UnbalancedTeams));
     }
@@ -311,22 +531,290 @@ mod tests {
     #[test]
     fn can_start_with_four_ready_players() {
         let mut game_lobby = setup_lobby();
-        let user = User::new("John Doe");
-        let user2 = User::new("Jane Doe");
-        let user3 = User::new("Dolly");
-        let user4 = User::new("Homer");
-
-        game_lobby.add_user(&user);
-        game_lobby.add_user(&user2);
-        game_lobby.add_user(&user3);
-        game_lobby.add_user(&user4);
-        game_lobby.ready_up(&user);
-        game_lobby.ready_up(&user2);
-        game_lobby.ready_up(&user3);
-        game_lobby.ready_up(&user4);
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+        let user4 = Arc::new(User::new("Homer"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.add_user(user4.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.ready_up(&user3).unwrap();
+        game_lobby.ready_up(&user4).unwrap();
 
         let result = game_lobby.start_game();
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn denies_joining_a_full_team() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+
+        assert_eq!(game_lobby.change_team(&user2, Team::Lajvarna), Err(GameError::TeamFull));
+        assert_eq!(game_lobby.change_team(&user, Team::Lajvarna), Ok(()));
+    }
+
+    #[test]
+    fn denies_actions_from_users_not_in_lobby() {
+        let mut game_lobby = setup_lobby();
+        let user = User::new("John Doe");
+
+        assert_eq!(game_lobby.ready_up(&user), Err(GameError::NotInLobby));
+        assert_eq!(game_lobby.unready(&user), Err(GameError::NotInLobby));
+        assert_eq!(game_lobby.change_team(&user, Team::Gottarna), Err(GameError::NotInLobby));
+    }
+
+    #[test]
+    fn denies_changes_after_start() {
+        let mut game_lobby = setup_lobby();
+        let users: Vec<Arc<User>> = ["A", "B", "C", "D"].into_iter()
+            .map(|name| Arc::new(User::new(name)))
+            .collect();
+        users.iter().for_each(|u| game_lobby.add_user(u.clone()).unwrap());
+        users.iter().for_each(|u| game_lobby.ready_up(u).unwrap());
+
+        game_lobby.start_game().unwrap();
+
+        assert!(game_lobby.is_started());
+        assert_eq!(game_lobby.start_game(), Err(GameError::AlreadyStarted));
+        assert_eq!(game_lobby.unready(&users[0]), Err(GameError::AlreadyStarted));
+        assert_eq!(
+            game_lobby.add_user(Arc::new(User::new("E"))),
+            Err(GameError::AlreadyStarted),
+        );
+    }
+
+    #[test]
+    fn first_user_to_join_hosts() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+
+        assert_eq!(game_lobby.host().unwrap().user(), &*user);
+    }
+
+    #[test]
+    fn host_is_transferred_when_host_leaves() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.del_user(&user).unwrap();
+
+        assert_eq!(game_lobby.host().unwrap().user(), &*user2);
+
+        game_lobby.del_user(&user2).unwrap();
+
+        assert_eq!(game_lobby.host(), None);
+    }
+
+    #[test]
+    fn host_can_hand_over_hosting() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+
+        assert_eq!(game_lobby.transfer_host(&user2, &user), Err(GameError::NotHost));
+        assert_eq!(game_lobby.transfer_host(&user, &user2), Ok(()));
+        assert_eq!(game_lobby.host().unwrap().user(), &*user2);
+    }
+
+    #[test]
+    fn host_can_kick_players() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+
+        assert_eq!(game_lobby.kick(&user2, &user), Err(GameError::NotHost));
+        assert_eq!(game_lobby.kick(&user, &user2), Ok(()));
+        assert_eq!(game_lobby.players().len(), 1);
+    }
+
+    #[test]
+    fn locked_lobby_denies_joining() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+
+        assert_eq!(game_lobby.lock(&user2), Err(GameError::NotHost));
+        game_lobby.lock(&user).unwrap();
+        assert!(game_lobby.is_locked());
+        assert_eq!(game_lobby.add_user(user2.clone()), Err(GameError::LobbyLocked));
+
+        game_lobby.unlock(&user).unwrap();
+        assert_eq!(game_lobby.add_user(user2.clone()), Ok(()));
+    }
+
+    #[test]
+    fn changing_team_unreadies_everyone() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.change_team(&user, Team::Lajvarna).unwrap();
+
+        assert_eq!(game_lobby.ready_count(), 2);
+
+        game_lobby.change_team(&user, Team::Gottarna).unwrap();
+
+        assert_eq!(game_lobby.ready_count(), 0);
+    }
+
+    #[test]
+    fn leaving_unreadies_everyone() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+        let user3 = Arc::new(User::new("Dolly"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.add_user(user3.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+        game_lobby.del_user(&user3).unwrap();
+
+        assert_eq!(game_lobby.ready_count(), 0);
+    }
+
+    #[test]
+    fn players_choose_seats_and_teams_follow() {
+        let mut game_lobby = Lobby::new(Settings { to_win: 13, seed: None, seating: SeatingMode::Choose, ..Settings::default() });
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.choose_seat(&user, Seat::East).unwrap();
+        game_lobby.choose_seat(&user2, Seat::West).unwrap();
+
+        assert_eq!(game_lobby.players[0].seat(), Some(Seat::East));
+        assert_eq!(game_lobby.players[0].team(), Team::Gottarna);
+        assert_eq!(game_lobby.players[1].team(), Team::Gottarna);
+        assert_eq!(game_lobby.choose_seat(&user2, Seat::East), Err(GameError::SeatTaken));
+        assert_eq!(game_lobby.change_team(&user, Team::Lajvarna), Err(GameError::WrongSeatingMode));
+    }
+
+    #[test]
+    fn denies_choosing_seats_when_seats_are_drawn() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+
+        assert_eq!(game_lobby.choose_seat(&user, Seat::North), Err(GameError::WrongSeatingMode));
+    }
+
+    #[test]
+    fn denies_start_until_every_seat_is_chosen() {
+        let mut game_lobby = Lobby::new(Settings { to_win: 13, seed: None, seating: SeatingMode::Choose, ..Settings::default() });
+        let users: Vec<Arc<User>> = ["A", "B", "C", "D"].iter()
+            .map(|name| Arc::new(User::new(name)))
+            .collect();
+
+        users.iter().for_each(|u| game_lobby.add_user(u.clone()).unwrap());
+        users.iter().take(3).zip(Seat::all()).for_each(|(u, seat)| game_lobby.choose_seat(u, seat).unwrap());
+        users.iter().for_each(|u| game_lobby.ready_up(u).unwrap());
+
+        assert_eq!(game_lobby.start_game(), Err(GameError::SeatsNotChosen));
+
+        game_lobby.choose_seat(&users[3], Seat::West).unwrap();
+        users.iter().for_each(|u| game_lobby.ready_up(u).unwrap());
+
+        assert!(game_lobby.start_game().is_ok());
+    }
+
+    #[test]
+    fn host_can_update_settings() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+        let user2 = Arc::new(User::new("Jane Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+        game_lobby.add_user(user2.clone()).unwrap();
+        game_lobby.ready_up(&user).unwrap();
+        game_lobby.ready_up(&user2).unwrap();
+
+        assert_eq!(game_lobby.update_settings(&user2, Settings::short_game()), Err(GameError::NotHost));
+        assert_eq!(game_lobby.ready_count(), 2);
+        assert_eq!(game_lobby.update_settings(&user, Settings::short_game()), Ok(()));
+        assert_eq!(game_lobby.settings(), &Settings::short_game());
+        assert_eq!(game_lobby.ready_count(), 0);
+    }
+
+    #[test]
+    fn denies_invalid_settings() {
+        let mut game_lobby = setup_lobby();
+        let user = Arc::new(User::new("John Doe"));
+
+        game_lobby.add_user(user.clone()).unwrap();
+
+        assert_eq!(
+            game_lobby.update_settings(&user, Settings { to_win: 0, ..Settings::default() }),
+            Err(GameError::InvalidTarget),
+        );
+        assert_eq!(game_lobby.settings().to_win, 13);
+    }
+
+    #[test]
+    fn spectators_join_without_taking_a_seat() {
+        let mut game_lobby = setup_lobby();
+        let users: Vec<Arc<User>> = ["A", "B", "C", "D"].iter()
+            .map(|name| Arc::new(User::new(name)))
+            .collect();
+        let spectator = Arc::new(User::new("Kibitzer"));
+
+        users.iter().for_each(|u| game_lobby.add_user(u.clone()).unwrap());
+        game_lobby.add_spectator(spectator.clone()).unwrap();
+        users.iter().for_each(|u| game_lobby.ready_up(u).unwrap());
+
+        assert_eq!(game_lobby.add_user(spectator.clone()), Err(GameError::AlreadyJoined));
+        assert_eq!(game_lobby.add_spectator(users[0].clone()), Err(GameError::AlreadyJoined));
+        assert_eq!(game_lobby.ready_up(&spectator), Err(GameError::Spectating));
+
+        let game = game_lobby.start_game().unwrap();
+
+        assert_eq!(game.spectators()[0], spectator);
+        assert_eq!(game.seat_of(&spectator), Err(GameError::Spectating));
+    }
+
+    #[test]
+    fn spectators_can_leave() {
+        let mut game_lobby = setup_lobby();
+        let spectator = Arc::new(User::new("Kibitzer"));
+
+        game_lobby.add_spectator(spectator.clone()).unwrap();
+
+        assert_eq!(game_lobby.del_spectator(&spectator), Ok(()));
+        assert_eq!(game_lobby.del_spectator(&spectator), Err(GameError::NotSpectating));
+        assert!(game_lobby.spectators().is_empty());
+    }
 }
@@ -0,0 +1,510 @@
+// A text format for deals in the style of the Portable Bridge Notation:
+//
+// [Dealer "N"]
+// [Deal "N:AKT4.2.QJ9.8765 ..."]
+// [Auction "E"]
+// Pass Play
+// [Contract "Grand"]
+// [Declarer "S"]
+// [Play "W"]
+// AS 2S 3S 4S
+// [Result "NS 7 EW 6"]
+//
+// Hands are written spades.hearts.diamonds.clubs, clockwise from the seat before the colon.
+// The auction and the play are listed after their tags, the play one trick per line in the
+// order the cards were played. Deals in a file are separated by an empty line.
+use crate::card::{Card, Notation, Rank, Suit};
+use crate::errors::{GameError, ParseCardError, PbnError};
+use crate::game::bid_round::{Bid, BidRound};
+use crate::game::contract::Contract;
+use crate::game::play_round::PlayRound;
+use crate::game::seat::Seat;
+use crate::hand::Hand;
+use std::fmt;
+use std::str::FromStr;
+
+// A deal from the first bid up to the last card played, always one that can be replayed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DealRecord {
+    dealer: Seat,
+    // Indexed by seat, as they were dealt
+    hands: Vec<Hand>,
+    bids: Vec<(Seat, Bid)>,
+    plays: Vec<Card>,
+}
+
+impl DealRecord {
+    pub fn from_bid_round(bid_round: &BidRound) -> DealRecord {
+        DealRecord {
+            dealer: bid_round.dealer(),
+            hands: Seat::all().into_iter().map(|seat| bid_round.hand(seat).clone()).collect(),
+            bids: bid_round.bids().to_vec(),
+            plays: Vec::new(),
+        }
+    }
+
+    // The cards played so far are put back in the hands they came from
+    pub fn from_play_round(play_round: &PlayRound) -> DealRecord {
+        let plays: Vec<(Seat, Card)> = play_round.tricks().iter()
+            .chain(std::iter::once(play_round.current_trick()))
+            .flat_map(|trick| trick.plays())
+            .collect();
+        let mut hands = play_round.hands().to_vec();
+        plays.iter().for_each(|(seat, card)| hands[seat.index()].add_card(*card));
+
+        DealRecord {
+            dealer: play_round.dealer(),
+            hands,
+            bids: play_round.bids().to_vec(),
+            plays: plays.into_iter().map(|(_, card)| card).collect(),
+        }
+    }
+
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    pub fn hands(&self) -> &[Hand] {
+        &self.hands
+    }
+
+    pub fn bids(&self) -> &[(Seat, Bid)] {
+        &self.bids
+    }
+
+    // Every card played, in order
+    pub fn plays(&self) -> &[Card] {
+        &self.plays
+    }
+
+    pub fn contract(&self) -> Option<Contract> {
+        self.bid_round().contract()
+    }
+
+    // The bidding as it stood at the end of the record
+    pub fn bid_round(&self) -> BidRound {
+        self.replay_bidding().expect("A record should always replay")
+    }
+
+    // The play as it stood at the end of the record, none if the bidding never finished
+    pub fn play_round(&self) -> Option<PlayRound> {
+        self.replay_play().expect("A record should always replay")
+    }
+
+    fn replay_bidding(&self) -> Result<BidRound, GameError> {
+        let mut bid_round = BidRound::from_hands(self.dealer, self.hands.clone())?;
+        for (seat, bid) in self.bids.iter() {
+            bid_round.register_bid(*seat, *bid)?;
+        }
+
+        Ok(bid_round)
+    }
+
+    fn replay_play(&self) -> Result<Option<PlayRound>, GameError> {
+        let bid_round = self.replay_bidding()?;
+        if bid_round.contract().is_none() {
+            return match self.plays.is_empty() {
+                true => Ok(None),
+                false => Err(GameError::BiddingNotFinished),
+            };
+        }
+
+        let mut play_round = bid_round.start_play()?;
+        for card in self.plays.iter() {
+            let seat = play_round.turn().ok_or(GameError::RoundFinished)?;
+            play_round.play_card(seat, *card)?;
+        }
+
+        Ok(Some(play_round))
+    }
+}
+
+impl fmt::Display for DealRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hands: Vec<String> = self.dealer.play_order().into_iter()
+            .map(|seat| write_hand(&self.hands[seat.index()]))
+            .collect();
+        let bids: Vec<&str> = self.bids.iter()
+            .map(|(_, bid)| match bid {
+                Bid::Pass => "Pass",
+                Bid::Play => "Play",
+            })
+            .collect();
+
+        writeln!(f, "[Dealer \"{}\"]", seat_letter(self.dealer))?;
+        writeln!(f, "[Deal \"{}:{}\"]", seat_letter(self.dealer), hands.join(" "))?;
+        writeln!(f, "[Auction \"{}\"]", seat_letter(self.dealer.left_hand_opponent()))?;
+        if !bids.is_empty() {
+            writeln!(f, "{}", bids.join(" "))?;
+        }
+
+        let Some(play_round) = self.play_round() else {
+            return Ok(());
+        };
+        match play_round.contract() {
+            Contract::Grand { bidder } => {
+                writeln!(f, "[Contract \"Grand\"]")?;
+                writeln!(f, "[Declarer \"{}\"]", seat_letter(bidder))?;
+            }
+            Contract::Nolo => writeln!(f, "[Contract \"Nolo\"]")?,
+        }
+
+        if self.plays.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "[Play \"{}\"]", seat_letter(play_round.contract().opening_leader(self.dealer)))?;
+        for trick in self.plays.chunks(4) {
+            let cards: Vec<String> = trick.iter().map(|card| card.to_string()).collect();
+            writeln!(f, "{}", cards.join(" "))?;
+        }
+
+        if play_round.is_finished() {
+            writeln!(
+                f,
+                "[Result \"NS {} EW {}\"]",
+                play_round.tricks_won_by_side(Seat::North),
+                play_round.tricks_won_by_side(Seat::East),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for DealRecord {
+    type Err = PbnError;
+
+    // Unknown tags are skipped, the contract and result are checked against the replayed deal
+    fn from_str(text: &str) -> Result<DealRecord, PbnError> {
+        let tags = read_tags(text)?;
+        let tag = |name: &str| tags.iter().find(|tag| tag.name.eq_ignore_ascii_case(name));
+        let required = |name: &str| tag(name).ok_or(PbnError::MissingTag);
+
+        let dealer = parse_seat(&required("Dealer")?.value)?;
+        let hands = parse_deal(&required("Deal")?.value)?;
+
+        let mut bids = Vec::new();
+        if let Some(auction) = tag("Auction") {
+            if parse_seat(&auction.value)? != dealer.left_hand_opponent() {
+                return Err(PbnError::InvalidValue);
+            }
+            if auction.section.len() > 4 {
+                return Err(GameError::BiddingFinished.into());
+            }
+            for (word, seat) in auction.section.iter().zip(dealer.left_hand_opponent().play_order()) {
+                bids.push((seat, parse_bid(word)?));
+            }
+        }
+
+        let mut plays = Vec::new();
+        if let Some(play) = tag("Play") {
+            plays = play.section.iter()
+                .map(|word| word.parse())
+                .collect::<Result<Vec<Card>, ParseCardError>>()?;
+        }
+
+        let record = DealRecord { dealer, hands, bids, plays };
+        let play_round = record.replay_play()?;
+        let contract = play_round.as_ref().map(|play_round| play_round.contract());
+
+        if let Some(contract_tag) = tag("Contract") {
+            let written = match contract_tag.value.to_lowercase().as_str() {
+                "grand" => Contract::Grand {
+                    bidder: parse_seat(&required("Declarer")?.value)?,
+                },
+                "nolo" => Contract::Nolo,
+                _ => return Err(PbnError::InvalidValue),
+            };
+            if contract != Some(written) {
+                return Err(GameError::ReplayMismatch.into());
+            }
+        }
+
+        if let (Some(play_tag), Some(play_round)) = (tag("Play"), play_round.as_ref()) {
+            if parse_seat(&play_tag.value)? != play_round.contract().opening_leader(dealer) {
+                return Err(PbnError::InvalidValue);
+            }
+        }
+
+        if let Some(result_tag) = tag("Result") {
+            let play_round = play_round.ok_or(GameError::RoundNotFinished)?;
+            let result = format!(
+                "NS {} EW {}",
+                play_round.tricks_won_by_side(Seat::North),
+                play_round.tricks_won_by_side(Seat::East),
+            );
+            let written: Vec<&str> = result_tag.value.split_whitespace().collect();
+            if !play_round.is_finished() || written.join(" ") != result {
+                return Err(GameError::ReplayMismatch.into());
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+// Reads every deal of a file
+pub fn read_all(text: &str) -> Result<Vec<DealRecord>, PbnError> {
+    let mut records = Vec::new();
+    let mut block = Vec::new();
+
+    for line in text.lines().chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            block.push(line);
+            continue;
+        }
+
+        let deal = block.join("\n");
+        block.clear();
+        if !read_tags(&deal)?.is_empty() {
+            records.push(deal.parse()?);
+        }
+    }
+
+    Ok(records)
+}
+
+pub fn write_all(records: &[DealRecord]) -> String {
+    records.iter()
+        .map(|record| record.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+struct Tag {
+    name: String,
+    value: String,
+    // The words on the lines that follow the tag
+    section: Vec<String>,
+}
+
+// Lines starting with % are comments
+fn read_tags(text: &str) -> Result<Vec<Tag>, PbnError> {
+    let mut tags: Vec<Tag> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+
+        match line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            Some(tag) => {
+                let (name, value) = tag.split_once(char::is_whitespace).ok_or(PbnError::Syntax)?;
+                let value = value.trim()
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or(PbnError::Syntax)?;
+
+                tags.push(Tag {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    section: Vec::new(),
+                });
+            }
+            None => tags.last_mut()
+                .ok_or(PbnError::Syntax)?
+                .section
+                .extend(line.split_whitespace().map(str::to_string)),
+        }
+    }
+
+    Ok(tags)
+}
+
+fn seat_letter(seat: Seat) -> char {
+    match seat {
+        Seat::North => 'N',
+        Seat::East => 'E',
+        Seat::South => 'S',
+        Seat::West => 'W',
+    }
+}
+
+fn parse_seat(text: &str) -> Result<Seat, PbnError> {
+    Seat::all().into_iter()
+        .find(|seat| text.trim().eq_ignore_ascii_case(&seat_letter(*seat).to_string()))
+        .ok_or(PbnError::InvalidValue)
+}
+
+fn parse_bid(text: &str) -> Result<Bid, PbnError> {
+    match text.to_lowercase().as_str() {
+        "pass" => Ok(Bid::Pass),
+        "play" => Ok(Bid::Play),
+        _ => Err(PbnError::InvalidValue),
+    }
+}
+
+const PBN_SUITS: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+fn write_hand(hand: &Hand) -> String {
+    PBN_SUITS.iter()
+        .map(|suit| {
+            hand.card_set().suit(*suit).iter()
+                .rev()
+                .map(|card| card.rank().format(Notation::Ascii))
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+fn parse_hand(text: &str) -> Result<Hand, PbnError> {
+    let suits: Vec<&str> = text.split('.').collect();
+    if suits.len() != 4 {
+        return Err(PbnError::InvalidValue);
+    }
+
+    let mut hand = Hand::new();
+    for (suit, ranks) in PBN_SUITS.iter().zip(suits) {
+        for symbol in ranks.replace("10", "T").chars() {
+            let card = Card::new(*suit, symbol.to_string().parse::<Rank>()?);
+            if hand.contains(&card) {
+                return Err(ParseCardError::DuplicateCard.into());
+            }
+            hand.add_card(card);
+        }
+    }
+
+    Ok(hand)
+}
+
+// The seat before the colon holds the first hand, the others follow clockwise
+fn parse_deal(text: &str) -> Result<Vec<Hand>, PbnError> {
+    let (first, hands) = text.split_once(':').ok_or(PbnError::InvalidValue)?;
+    let first = parse_seat(first)?;
+    let hands: Vec<&str> = hands.split_whitespace().collect();
+    if hands.len() != 4 {
+        return Err(PbnError::InvalidValue);
+    }
+
+    let mut dealt = vec![Hand::new(); 4];
+    for (seat, hand) in first.play_order().into_iter().zip(hands) {
+        dealt[seat.index()] = parse_hand(hand)?;
+    }
+
+    Ok(dealt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn setup_bid_round(dealer: Seat) -> BidRound {
+        BidRound::with_rng(dealer, &mut StdRng::seed_from_u64(3))
+    }
+
+    fn play_out(play_round: &mut PlayRound) {
+        while let Some(seat) = play_round.turn() {
+            let card = play_round.legal_cards(seat)[0];
+            play_round.play_card(seat, card).unwrap();
+        }
+    }
+
+    #[test]
+    fn writes_a_played_deal() {
+        let mut hands = vec![Hand::new(); 4];
+        // North gets all the spades, east the hearts, south the diamonds and west the clubs
+        for (hand, suit) in hands.iter_mut().zip(PBN_SUITS) {
+            Rank::all().into_iter().for_each(|rank| hand.add_card(Card::new(suit, rank)));
+        }
+        let mut bid_round = BidRound::from_hands(Seat::West, hands).unwrap();
+        bid_round.register_bid(Seat::North, Bid::Play).unwrap();
+        let mut play_round = bid_round.start_play().unwrap();
+        play_out(&mut play_round);
+
+        let text = DealRecord::from_play_round(&play_round).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "[Dealer \"W\"]");
+        assert_eq!(lines[1], "[Deal \"W:...AKQJT98765432 AKQJT98765432... .AKQJT98765432.. ..AKQJT98765432.\"]");
+        assert_eq!(lines[2], "[Auction \"N\"]");
+        assert_eq!(lines[3], "Play");
+        assert_eq!(lines[4], "[Contract \"Grand\"]");
+        assert_eq!(lines[5], "[Declarer \"N\"]");
+        assert_eq!(lines[6], "[Play \"E\"]");
+        assert_eq!(lines[7], "2H 2D 2C 2S");
+        assert_eq!(lines.last(), Some(&"[Result \"NS 0 EW 13\"]"));
+    }
+
+    #[test]
+    fn round_trips_through_the_play() {
+        let mut bid_round = setup_bid_round(Seat::South);
+        bid_round.register_bid(Seat::West, Bid::Pass).unwrap();
+        bid_round.register_bid(Seat::North, Bid::Play).unwrap();
+        let mut play_round = bid_round.start_play().unwrap();
+        play_out(&mut play_round);
+
+        let record = DealRecord::from_play_round(&play_round);
+        let read: DealRecord = record.to_string().parse().unwrap();
+
+        assert_eq!(record.bids(), play_round.bids());
+        assert_eq!(read, record);
+        assert_eq!(read.play_round(), Some(play_round));
+    }
+
+    #[test]
+    fn round_trips_unfinished_deals() {
+        let mut bid_round = setup_bid_round(Seat::North);
+        let record = DealRecord::from_bid_round(&bid_round);
+
+        assert_eq!(record.to_string().parse(), Ok(record.clone()));
+        assert_eq!(record.bid_round(), bid_round);
+        assert_eq!(record.play_round(), None);
+
+        bid_round.register_bid(Seat::East, Bid::Pass).unwrap();
+        let record = DealRecord::from_bid_round(&bid_round);
+        assert_eq!(record.to_string().parse::<DealRecord>().unwrap().bid_round(), bid_round);
+
+        for seat in [Seat::South, Seat::West, Seat::North] {
+            bid_round.register_bid(seat, Bid::Pass).unwrap();
+        }
+        let mut play_round = bid_round.start_play().unwrap();
+        let card = play_round.legal_cards(Seat::East)[0];
+        play_round.play_card(Seat::East, card).unwrap();
+
+        let record = DealRecord::from_play_round(&play_round);
+        let read: DealRecord = record.to_string().parse().unwrap();
+
+        assert_eq!(read.contract(), Some(Contract::Nolo));
+        assert_eq!(read.play_round(), Some(play_round));
+    }
+
+    #[test]
+    fn reads_and_writes_many_deals() {
+        let records = vec![
+            DealRecord::from_bid_round(&setup_bid_round(Seat::North)),
+            DealRecord::from_bid_round(&setup_bid_round(Seat::East)),
+        ];
+        let text = format!("% Club night\n\n{}", write_all(&records));
+
+        assert_eq!(read_all(&text), Ok(records));
+    }
+
+    #[test]
+    fn denies_broken_records() {
+        let text = DealRecord::from_bid_round(&setup_bid_round(Seat::North)).to_string();
+        let deal = text.lines().nth(1).unwrap();
+
+        assert_eq!("Pass".parse::<DealRecord>(), Err(PbnError::Syntax));
+        assert_eq!("[Dealer N]".parse::<DealRecord>(), Err(PbnError::Syntax));
+        assert_eq!(deal.parse::<DealRecord>(), Err(PbnError::MissingTag));
+        assert_eq!(
+            format!("[Dealer \"X\"]\n{}", deal).parse::<DealRecord>(),
+            Err(PbnError::InvalidValue),
+        );
+        assert_eq!(
+            format!("[Dealer \"N\"]\n{}", deal.replacen('A', "Z", 1)).parse::<DealRecord>(),
+            Err(PbnError::Card(ParseCardError::InvalidRank)),
+        );
+        assert_eq!(
+            "[Dealer \"N\"]\n[Deal \"N:AKQ... ... ... ...\"]".parse::<DealRecord>(),
+            Err(PbnError::Game(GameError::InvalidDeal)),
+        );
+        assert_eq!(
+            format!("{}[Contract \"Nolo\"]\n", text).parse::<DealRecord>(),
+            Err(PbnError::Game(GameError::ReplayMismatch)),
+        );
+    }
+}
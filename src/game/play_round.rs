@@ -0,0 +1,559 @@
+use crate::card::{Card, Suit};
+use crate::card_set::CardSet;
+use crate::errors::GameError;
+use crate::game::bid_round::Bid;
+use crate::game::contract::Contract;
+use crate::game::seat::Seat;
+use crate::hand::Hand;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trick {
+    leader: Seat,
+    cards: Vec<Card>,
+}
+
+impl Trick {
+    pub fn new(leader: Seat) -> Trick {
+        Trick {
+            leader,
+            cards: Vec::with_capacity(4),
+        }
+    }
+
+    pub fn leader(&self) -> Seat {
+        self.leader
+    }
+
+    // Cards in the order they were played, starting with the leader
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn led_suit(&self) -> Option<Suit> {
+        self.cards.first().map(|c| *c.suit())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.cards.len() == 4
+    }
+
+    pub fn next_player(&self) -> Seat {
+        Seat::from_index(self.leader.index() + self.cards.len())
+    }
+
+    // The cards played together with the seat that played them
+    pub fn plays(&self) -> Vec<(Seat, Card)> {
+        self.leader.play_order().into_iter()
+            .zip(self.cards.iter().cloned())
+            .collect()
+    }
+
+    fn add_card(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+
+    // There are no trumps, the highest card of the led suit takes the trick
+    pub fn winner(&self) -> Option<Seat> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let led_suit = self.led_suit()?;
+        let (offset, _) = self.cards.iter()
+            .enumerate()
+            .filter(|(_, c)| *c.suit() == led_suit)
+            .max_by_key(|(_, c)| *c.rank())?;
+
+        Some(Seat::from_index(self.leader.index() + offset))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::PlayRoundData"))]
+pub struct PlayRound {
+    hands: Vec<Hand>,
+    dealer: Seat,
+    contract: Contract,
+    bids: Vec<(Seat, Bid)>,
+    current_trick: Trick,
+    tricks: Vec<Trick>,
+    played: CardSet,
+}
+
+impl PlayRound {
+    pub(crate) fn new(hands: Vec<Hand>, dealer: Seat, contract: Contract) -> PlayRound {
+        let leader = contract.opening_leader(dealer);
+
+        PlayRound {
+            hands,
+            dealer,
+            contract,
+            bids: Vec::new(),
+            current_trick: Trick::new(leader),
+            tricks: Vec::with_capacity(13),
+            played: CardSet::new(),
+        }
+    }
+
+    // Keeps the bidding that led to the contract
+    pub(crate) fn with_bids(mut self, bids: Vec<(Seat, Bid)>) -> PlayRound {
+        self.bids = bids;
+        self
+    }
+
+    pub fn hands(&self) -> &[Hand] {
+        &self.hands
+    }
+
+    pub fn hand(&self, seat: Seat) -> &Hand {
+        &self.hands[seat.index()]
+    }
+
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    pub fn bids(&self) -> &[(Seat, Bid)] {
+        &self.bids
+    }
+
+    pub fn current_trick(&self) -> &Trick {
+        &self.current_trick
+    }
+
+    pub fn tricks(&self) -> &[Trick] {
+        &self.tricks
+    }
+
+    // Every card that has been played this deal, including the current trick
+    pub fn played(&self) -> CardSet {
+        self.played
+    }
+
+    pub fn leader(&self) -> Seat {
+        self.current_trick.leader()
+    }
+
+    pub fn turn(&self) -> Option<Seat> {
+        if self.is_finished() {
+            return None;
+        }
+
+        Some(self.current_trick.next_player())
+    }
+
+    pub fn last_trick_winner(&self) -> Option<Seat> {
+        self.tricks.last().and_then(|t| t.winner())
+    }
+
+    pub fn tricks_won(&self, player: Seat) -> usize {
+        self.tricks.iter()
+            .filter(|t| t.winner() == Some(player))
+            .count()
+    }
+
+    pub fn tricks_won_by_side(&self, player: Seat) -> usize {
+        self.tricks_won(player) + self.tricks_won(player.partner())
+    }
+
+    pub fn legal_cards(&self, player: Seat) -> Vec<Card> {
+        if self.turn() != Some(player) {
+            return Vec::new();
+        }
+
+        self.hands[player.index()].legal_cards(self.current_trick.led_suit())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tricks.len() == 13
+    }
+
+    // Returns the winner of the trick once the fourth card has been played
+    pub fn play_card(&mut self, player: Seat, card: Card) -> Result<Option<Seat>, GameError> {
+        if self.is_finished() {
+            return Err(GameError::RoundFinished);
+        }
+
+        if self.turn() != Some(player) {
+            return Err(GameError::NotPlayersTurn);
+        }
+
+        self.hands[player.index()].check_play(&card, self.current_trick.led_suit())?;
+        let card = self.hands[player.index()]
+            .remove_card(&card)
+            .ok_or(GameError::CardNotInHand)?;
+        self.played.insert(card);
+        self.current_trick.add_card(card);
+
+        if !self.current_trick.is_complete() {
+            return Ok(None);
+        }
+
+        let winner = self.current_trick.winner()
+            .expect("A complete trick should have a winner");
+        let trick = std::mem::replace(&mut self.current_trick, Trick::new(winner));
+        self.tricks.push(trick);
+
+        Ok(Some(winner))
+    }
+
+    // The player on lead claims the remaining tricks. Only allowed when every card
+    // they hold beats every card left of its suit, the tricks are then played out
+    pub fn claim(&mut self, player: Seat) -> Result<Vec<(Seat, Card)>, GameError> {
+        if self.is_finished() {
+            return Err(GameError::RoundFinished);
+        }
+
+        if self.turn() != Some(player) {
+            return Err(GameError::NotPlayersTurn);
+        }
+
+        // Every card still out is in one of the hands
+        let own = self.hand(player).card_set();
+        let others = CardSet::full() - self.played - own;
+        let unbeatable = Suit::all().into_iter().all(|suit| {
+            match (own.suit(suit).lowest(), others.suit(suit).highest()) {
+                (Some(lowest), Some(highest)) => lowest.rank() > highest.rank(),
+                _ => true,
+            }
+        });
+
+        if !self.current_trick.cards().is_empty() || !unbeatable {
+            return Err(GameError::InvalidClaim);
+        }
+
+        let mut plays = Vec::new();
+        while let Some(seat) = self.turn() {
+            let card = self.legal_cards(seat)[0];
+            self.play_card(seat, card)?;
+            plays.push((seat, card));
+        }
+
+        Ok(plays)
+    }
+}
+
+// Read by dealing the hands again and replaying the bids and cards, so a round that
+// couldn't come from legal play is turned down instead of breaking the game later
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{PlayRound, Trick};
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+    use crate::errors::GameError;
+    use crate::game::bid_round::{Bid, BidRound};
+    use crate::game::contract::Contract;
+    use crate::game::seat::Seat;
+    use crate::hand::Hand;
+
+    #[derive(serde::Deserialize)]
+    pub(super) struct PlayRoundData {
+        hands: Vec<Hand>,
+        dealer: Seat,
+        contract: Contract,
+        bids: Vec<(Seat, Bid)>,
+        current_trick: Trick,
+        tricks: Vec<Trick>,
+        played: CardSet,
+    }
+
+    impl TryFrom<PlayRoundData> for PlayRound {
+        type Error = GameError;
+
+        fn try_from(data: PlayRoundData) -> Result<PlayRound, GameError> {
+            if data.hands.len() != 4 {
+                return Err(GameError::InvalidDeal);
+            }
+
+            // The hands as dealt are the cards left with the played ones put back
+            let plays: Vec<(Seat, Card)> = data.tricks.iter()
+                .chain(std::iter::once(&data.current_trick))
+                .flat_map(|trick| trick.plays())
+                .collect();
+            let mut hands = data.hands.clone();
+            plays.iter().for_each(|(seat, card)| hands[seat.index()].add_card(*card));
+
+            let mut bid_round = BidRound::from_hands(data.dealer, hands)?;
+            for (seat, bid) in data.bids {
+                bid_round.register_bid(seat, bid)?;
+            }
+            if bid_round.contract() != Some(data.contract) {
+                return Err(GameError::ReplayMismatch);
+            }
+
+            let mut play_round = bid_round.start_play()?;
+            for (seat, card) in plays {
+                play_round.play_card(seat, card)?;
+            }
+
+            // Whatever the replay leaves out, like a fifth card in a trick
+            if play_round.hands != data.hands
+                || play_round.tricks != data.tricks
+                || play_round.current_trick != data.current_trick
+                || play_round.played != data.played
+            {
+                return Err(GameError::ReplayMismatch);
+            }
+
+            Ok(play_round)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Rank;
+    use crate::deck::Deck;
+    use crate::game::bid_round::BidRound;
+    use super::*;
+
+    fn setup_play_round() -> PlayRound {
+        let mut deck = Deck::new();
+        deck.shuffle();
+
+        PlayRound::new(deck.deal_hands(), Seat::North, Contract::Nolo)
+    }
+
+    fn card_to_play(play_round: &PlayRound, player: Seat) -> Card {
+        play_round.legal_cards(player)[0]
+    }
+
+    #[test]
+    fn trick_winner_is_highest_card_of_led_suit() {
+        let mut trick = Trick::new(Seat::East);
+        trick.add_card(Card::new(Suit::Hearts, Rank::Ten));
+        trick.add_card(Card::new(Suit::Spades, Rank::Ace));
+        trick.add_card(Card::new(Suit::Hearts, Rank::King));
+        trick.add_card(Card::new(Suit::Hearts, Rank::Two));
+
+        assert_eq!(trick.led_suit(), Some(Suit::Hearts));
+        assert_eq!(trick.winner(), Some(Seat::West));
+        assert_eq!(trick.plays()[3], (Seat::North, Card::new(Suit::Hearts, Rank::Two)));
+    }
+
+    #[test]
+    fn incomplete_trick_has_no_winner() {
+        let mut trick = Trick::new(Seat::North);
+        trick.add_card(Card::new(Suit::Hearts, Rank::Ten));
+
+        assert_eq!(trick.winner(), None);
+        assert_eq!(trick.next_player(), Seat::East);
+    }
+
+    #[test]
+    fn player_left_of_dealer_leads_nolo() {
+        let play_round = setup_play_round();
+
+        assert_eq!(play_round.leader(), Seat::East);
+        assert_eq!(play_round.turn(), Some(Seat::East));
+    }
+
+    #[test]
+    fn player_left_of_bidder_leads_grand() {
+        let mut deck = Deck::new();
+        deck.shuffle();
+        let play_round = PlayRound::new(
+            deck.deal_hands(),
+            Seat::North,
+            Contract::Grand { bidder: Seat::South },
+        );
+
+        assert_eq!(play_round.leader(), Seat::West);
+    }
+
+    #[test]
+    fn denies_playing_out_of_turn() {
+        let mut play_round = setup_play_round();
+        let card = play_round.hand(Seat::South).cards()[0];
+
+        assert_eq!(play_round.play_card(Seat::South, card), Err(GameError::NotPlayersTurn));
+    }
+
+    #[test]
+    fn denies_playing_card_not_in_hand() {
+        let mut play_round = setup_play_round();
+        let card = play_round.hand(Seat::South).cards()[0];
+
+        assert_eq!(play_round.play_card(Seat::East, card), Err(GameError::CardNotInHand));
+    }
+
+    #[test]
+    fn denies_breaking_follow_suit() {
+        let mut hands = vec![Hand::new(); 4];
+        hands[1].add_card(Card::new(Suit::Hearts, Rank::Ten));
+        hands[2].add_card(Card::new(Suit::Hearts, Rank::Two));
+        hands[2].add_card(Card::new(Suit::Spades, Rank::Ace));
+        let mut play_round = PlayRound::new(hands, Seat::North, Contract::Nolo);
+
+        play_round.play_card(Seat::East, Card::new(Suit::Hearts, Rank::Ten)).unwrap();
+
+        assert_eq!(play_round.legal_cards(Seat::South), vec![Card::new(Suit::Hearts, Rank::Two)]);
+        assert_eq!(
+            play_round.play_card(Seat::South, Card::new(Suit::Spades, Rank::Ace)),
+            Err(GameError::MustFollowSuit),
+        );
+    }
+
+    #[test]
+    fn no_legal_cards_when_not_on_turn() {
+        let play_round = setup_play_round();
+
+        assert!(play_round.legal_cards(Seat::South).is_empty());
+        assert_eq!(play_round.legal_cards(Seat::East).len(), 13);
+    }
+
+    #[test]
+    fn playing_a_card_passes_the_turn() {
+        let mut play_round = setup_play_round();
+        let card = card_to_play(&play_round, Seat::East);
+
+        assert_eq!(play_round.play_card(Seat::East, card), Ok(None));
+        assert_eq!(play_round.turn(), Some(Seat::South));
+        assert_eq!(play_round.hand(Seat::East).len(), 12);
+        assert_eq!(play_round.current_trick().cards(), &[card]);
+        assert!(play_round.played().contains(card));
+    }
+
+    #[test]
+    fn trick_winner_leads_next_trick() {
+        let mut play_round = setup_play_round();
+
+        let mut winner = None;
+        for _ in 0..4 {
+            let player = play_round.turn().unwrap();
+            let card = card_to_play(&play_round, player);
+            winner = play_round.play_card(player, card).unwrap();
+        }
+
+        assert!(winner.is_some());
+        assert_eq!(play_round.last_trick_winner(), winner);
+        assert_eq!(play_round.leader(), winner.unwrap());
+        assert_eq!(play_round.tricks().len(), 1);
+    }
+
+    #[test]
+    fn play_all_thirteen_tricks() {
+        let mut play_round = setup_play_round();
+
+        while let Some(player) = play_round.turn() {
+            let card = card_to_play(&play_round, player);
+            play_round.play_card(player, card).unwrap();
+        }
+
+        assert!(play_round.is_finished());
+        assert_eq!(play_round.tricks().len(), 13);
+        assert!(play_round.hands().iter().all(|h| h.is_empty()));
+        assert_eq!(play_round.played(), CardSet::full());
+        assert_eq!(Seat::all().into_iter().map(|s| play_round.tricks_won(s)).sum::<usize>(), 13);
+    }
+
+    #[test]
+    fn denies_playing_after_round_is_finished() {
+        let mut play_round = setup_play_round();
+
+        while let Some(player) = play_round.turn() {
+            let card = card_to_play(&play_round, player);
+            play_round.play_card(player, card).unwrap();
+        }
+
+        assert_eq!(
+            play_round.play_card(Seat::North, Card::new(Suit::Spades, Rank::Ace)),
+            Err(GameError::RoundFinished),
+        );
+    }
+
+    #[test]
+    fn claim_plays_out_the_remaining_tricks() {
+        // Every seat holds a full suit, so west on lead can't lose a trick
+        let mut hands = vec![Hand::new(); 4];
+        for (hand, suit) in hands.iter_mut().zip(Suit::all()) {
+            Rank::all().into_iter().for_each(|rank| hand.add_card(Card::new(suit, rank)));
+        }
+        let mut play_round = PlayRound::new(hands, Seat::South, Contract::Nolo);
+
+        let plays = play_round.claim(Seat::West).unwrap();
+
+        assert!(play_round.is_finished());
+        assert_eq!(plays.len(), 52);
+        assert_eq!(play_round.tricks_won(Seat::West), 13);
+    }
+
+    // West gets the given cards and the rest are dealt out to north, east and south.
+    // South deals and everyone passes, so west is on lead in nolo
+    fn play_round_with_west_holding(west: &[Card]) -> PlayRound {
+        let mut hands = vec![Hand::new(); 4];
+        west.iter().for_each(|card| hands[Seat::West.index()].add_card(*card));
+        let rest = CardSet::full() - hands[Seat::West.index()].card_set();
+        for (card, seat) in rest.iter().zip([Seat::North, Seat::East, Seat::South].into_iter().cycle()) {
+            hands[seat.index()].add_card(card);
+        }
+
+        let mut bid_round = BidRound::from_hands(Seat::South, hands).unwrap();
+        for seat in Seat::West.play_order() {
+            bid_round.register_bid(seat, Bid::Pass).unwrap();
+        }
+
+        bid_round.start_play().unwrap()
+    }
+
+    fn top_cards() -> Vec<Card> {
+        Suit::all().into_iter()
+            .flat_map(|suit| [Rank::Ace, Rank::King, Rank::Queen].map(|rank| Card::new(suit, rank)))
+            .collect()
+    }
+
+    #[test]
+    fn claim_with_the_top_cards_of_every_suit() {
+        let mut west = top_cards();
+        west.push(Card::new(Suit::Spades, Rank::Jack));
+        let mut play_round = play_round_with_west_holding(&west);
+
+        assert_eq!(play_round.turn(), Some(Seat::West));
+
+        let plays = play_round.claim(Seat::West).unwrap();
+
+        assert_eq!(plays.len(), 52);
+        assert_eq!(play_round.tricks_won(Seat::West), 13);
+    }
+
+    #[test]
+    fn denies_claims_that_can_be_beaten() {
+        // Every other spade beats west's two of spades
+        let mut west = top_cards();
+        west.push(Card::new(Suit::Spades, Rank::Two));
+        let mut play_round = play_round_with_west_holding(&west);
+
+        assert_eq!(play_round.claim(Seat::North), Err(GameError::NotPlayersTurn));
+        assert_eq!(play_round.claim(Seat::West), Err(GameError::InvalidClaim));
+
+        play_round.play_card(Seat::West, Card::new(Suit::Spades, Rank::Ace)).unwrap();
+
+        // No claims halfway through a trick
+        assert_eq!(play_round.claim(Seat::North), Err(GameError::InvalidClaim));
+        assert!(play_round.tricks().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reading_a_broken_play_round_is_an_error() {
+        let mut west = top_cards();
+        west.push(Card::new(Suit::Spades, Rank::Jack));
+        let mut play_round = play_round_with_west_holding(&west);
+        play_round.play_card(Seat::West, Card::new(Suit::Spades, Rank::Ace)).unwrap();
+        let json = serde_json::to_string(&play_round).unwrap();
+        let wrong_leader = json.replace(r#""leader":"west""#, r#""leader":"north""#);
+        let forgotten_card = json.replace(r#""played":["AS"]"#, r#""played":[]"#);
+
+        assert_eq!(serde_json::from_str::<PlayRound>(&json).unwrap(), play_round);
+        for broken in [wrong_leader, forgotten_card] {
+            assert_ne!(broken, json);
+            assert!(serde_json::from_str::<PlayRound>(&broken).is_err());
+        }
+    }
+}
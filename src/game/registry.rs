@@ -0,0 +1,306 @@
+use crate::errors::GameError;
+use crate::game::lobby::Lobby;
+use crate::game::{Game, Settings};
+use crate::user::User;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Leaves out characters that are easily mixed up, like 0 and O or 1 and I
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 5;
+// How long a lobby may stay empty, so its creator has time to join it
+const EMPTY_LOBBY_GRACE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LobbyId(pub u64);
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LobbySummary {
+    pub id: LobbyId,
+    pub code: String,
+    pub players: usize,
+    pub host: Option<String>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    code: String,
+    lobby: Lobby,
+    touched: Instant,
+}
+
+#[derive(Debug)]
+pub struct LobbyRegistry {
+    lobbies: HashMap<LobbyId, Entry>,
+    next_id: u64,
+    rng: StdRng,
+}
+
+impl LobbyRegistry {
+    pub fn new() -> LobbyRegistry {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn with_rng(rng: StdRng) -> LobbyRegistry {
+        LobbyRegistry {
+            lobbies: HashMap::new(),
+            next_id: 1,
+            rng,
+        }
+    }
+
+    pub fn create(&mut self, settings: Settings) -> Result<LobbyId, GameError> {
+        settings.validate()?;
+
+        let id = LobbyId(self.next_id);
+        self.next_id += 1;
+
+        let code = self.unused_code();
+        self.lobbies.insert(id, Entry {
+            code,
+            lobby: Lobby::new(settings),
+            touched: Instant::now(),
+        });
+
+        Ok(id)
+    }
+
+    pub fn lobby(&self, id: LobbyId) -> Option<&Lobby> {
+        self.lobbies.get(&id).map(|e| &e.lobby)
+    }
+
+    // Any change to a lobby counts as activity and keeps it from going stale
+    pub fn lobby_mut(&mut self, id: LobbyId) -> Option<&mut Lobby> {
+        self.lobbies.get_mut(&id).map(|e| {
+            e.touched = Instant::now();
+            &mut e.lobby
+        })
+    }
+
+    pub fn code(&self, id: LobbyId) -> Option<&str> {
+        self.lobbies.get(&id).map(|e| e.code.as_str())
+    }
+
+    // Codes are read out loud and typed in by hand, so case is ignored
+    pub fn find_by_code(&self, code: &str) -> Option<LobbyId> {
+        self.lobbies.iter()
+            .find(|(_, e)| e.code.eq_ignore_ascii_case(code.trim()))
+            .map(|(id, _)| *id)
+    }
+
+    pub fn join(&mut self, code: &str, user: Arc<User>) -> Result<LobbyId, GameError> {
+        let id = self.find_by_code(code).ok_or(GameError::LobbyNotFound)?;
+        self.lobby_mut(id)
+            .ok_or(GameError::LobbyNotFound)?
+            .add_user(user)?;
+
+        Ok(id)
+    }
+
+    // Lobbies that can still be joined, oldest first
+    pub fn open_lobbies(&self) -> Vec<LobbySummary> {
+        let mut open: Vec<LobbySummary> = self.lobbies.iter()
+            .filter(|(_, e)| {
+                !e.lobby.is_started() && !e.lobby.is_locked() && e.lobby.players().len() < 4
+            })
+            .map(|(id, e)| LobbySummary {
+                id: *id,
+                code: e.code.clone(),
+                players: e.lobby.players().len(),
+                host: e.lobby.host().map(|p| p.user().name().to_string()),
+            })
+            .collect();
+
+        open.sort_by_key(|summary| summary.id);
+        open
+    }
+
+    pub fn remove(&mut self, id: LobbyId) -> Option<Lobby> {
+        self.lobbies.remove(&id).map(|e| e.lobby)
+    }
+
+    // Removes lobbies that have been empty for a while and lobbies idle for at least max_idle
+    pub fn prune(&mut self, max_idle: Duration) -> Vec<LobbyId> {
+        self.prune_at(Instant::now(), max_idle)
+    }
+
+    // Starts the game and hands it out, the lobby and its code are freed
+    pub fn start_game(&mut self, id: LobbyId) -> Result<Game, GameError> {
+        let game = self.lobby_mut(id)
+            .ok_or(GameError::LobbyNotFound)?
+            .start_game()?;
+        self.lobbies.remove(&id);
+
+        Ok(game)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lobbies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lobbies.is_empty()
+    }
+
+    fn prune_at(&mut self, now: Instant, max_idle: Duration) -> Vec<LobbyId> {
+        let mut pruned: Vec<LobbyId> = self.lobbies.iter()
+            .filter(|(_, e)| {
+                let idle = now.saturating_duration_since(e.touched);
+                (e.lobby.players().is_empty() && idle >= EMPTY_LOBBY_GRACE) || idle >= max_idle
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        pruned.iter().for_each(|id| { self.lobbies.remove(id); });
+        pruned.sort();
+        pruned
+    }
+
+    fn unused_code(&mut self) -> String {
+        loop {
+            let code: String = (0..CODE_LENGTH)
+                .map(|_| CODE_ALPHABET[self.rng.gen_range(0..CODE_ALPHABET.len())] as char)
+                .collect();
+
+            if self.find_by_code(&code).is_none() {
+                return code;
+            }
+        }
+    }
+}
+
+impl Default for LobbyRegistry {
+    fn default() -> LobbyRegistry {
+        LobbyRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_registry() -> LobbyRegistry {
+        LobbyRegistry::with_rng(StdRng::seed_from_u64(7))
+    }
+
+    fn setup_users() -> Vec<Arc<User>> {
+        vec![
+            Arc::new(User::new("A")),
+            Arc::new(User::new("B")),
+            Arc::new(User::new("C")),
+            Arc::new(User::new("D")),
+        ]
+    }
+
+    #[test]
+    fn created_lobbies_get_unique_ids_and_codes() {
+        let mut registry = setup_registry();
+
+        let id1 = registry.create(Settings::default()).unwrap();
+        let id2 = registry.create(Settings::default()).unwrap();
+        let code = registry.code(id1).unwrap();
+
+        assert_ne!(id1, id2);
+        assert_ne!(registry.code(id1), registry.code(id2));
+        assert_eq!(code.len(), CODE_LENGTH);
+        assert!(code.bytes().all(|c| CODE_ALPHABET.contains(&c)));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn denies_creating_with_invalid_settings() {
+        let mut registry = setup_registry();
+
+        assert_eq!(
+            registry.create(Settings { to_win: 0, ..Settings::default() }),
+            Err(GameError::InvalidTarget),
+        );
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn join_by_code() {
+        let mut registry = setup_registry();
+        let user = Arc::new(User::new("John Doe"));
+        let id = registry.create(Settings::default()).unwrap();
+        let code = registry.code(id).unwrap().to_lowercase();
+
+        assert_eq!(registry.join(&code, user.clone()), Ok(id));
+        assert_eq!(registry.join("?????", user), Err(GameError::LobbyNotFound));
+        assert_eq!(registry.lobby(id).unwrap().players().len(), 1);
+    }
+
+    #[test]
+    fn lists_open_lobbies_with_occupancy() {
+        let mut registry = setup_registry();
+        let users = setup_users();
+        let open = registry.create(Settings::default()).unwrap();
+        let locked = registry.create(Settings::default()).unwrap();
+        let full = registry.create(Settings::default()).unwrap();
+
+        registry.lobby_mut(open).unwrap().add_user(users[0].clone()).unwrap();
+        registry.lobby_mut(locked).unwrap().add_user(users[0].clone()).unwrap();
+        registry.lobby_mut(locked).unwrap().lock(&users[0]).unwrap();
+        users.iter().for_each(|u| registry.lobby_mut(full).unwrap().add_user(u.clone()).unwrap());
+
+        assert_eq!(
+            registry.open_lobbies(),
+            vec![LobbySummary {
+                id: open,
+                code: registry.code(open).unwrap().to_string(),
+                players: 1,
+                host: Some("A".to_string()),
+            }],
+        );
+    }
+
+    #[test]
+    fn freshly_created_lobby_survives_a_prune() {
+        let mut registry = setup_registry();
+        let id = registry.create(Settings::default()).unwrap();
+
+        assert_eq!(registry.prune(Duration::from_secs(600)), vec![]);
+        assert!(registry.lobby(id).is_some());
+    }
+
+    #[test]
+    fn prunes_empty_and_stale_lobbies() {
+        let mut registry = setup_registry();
+        let user = Arc::new(User::new("John Doe"));
+        let empty = registry.create(Settings::default()).unwrap();
+        let active = registry.create(Settings::default()).unwrap();
+        registry.lobby_mut(active).unwrap().add_user(user).unwrap();
+
+        let after_grace = Instant::now() + EMPTY_LOBBY_GRACE;
+        assert_eq!(registry.prune_at(after_grace, Duration::from_secs(600)), vec![empty]);
+        assert!(registry.lobby(active).is_some());
+
+        let later = Instant::now() + Duration::from_secs(601);
+        assert_eq!(registry.prune_at(later, Duration::from_secs(600)), vec![active]);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn starting_hands_out_the_game() {
+        let mut registry = setup_registry();
+        let users = setup_users();
+        let id = registry.create(Settings::default()).unwrap();
+        let lobby = registry.lobby_mut(id).unwrap();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        assert_eq!(registry.start_game(id).err(), Some(GameError::RequiresFourReadyPlayers));
+
+        let lobby = registry.lobby_mut(id).unwrap();
+        users.iter().for_each(|u| lobby.ready_up(u).unwrap());
+        let game = registry.start_game(id).unwrap();
+
+        assert!(users.iter().all(|u| game.table().seat_of(u).is_some()));
+        assert_eq!(registry.lobby(id), None);
+        assert_eq!(registry.start_game(id).err(), Some(GameError::LobbyNotFound));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::errors::GameError;
+use crate::game::contract::Contract;
+use crate::game::play_round::PlayRound;
+use crate::game::seat::Seat;
+use crate::game::table::Table;
+use crate::game::{ScoringScheme, Team};
+
+// A side needs more than this many tricks before it scores
+const BOOK: usize = 6;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score {
+    lajvarna: u16,
+    gottarna: u16,
+}
+
+impl Score {
+    pub fn points(&self, team: Team) -> u16 {
+        match team {
+            Team::Lajvarna => self.lajvarna,
+            Team::Gottarna => self.gottarna,
+        }
+    }
+
+    pub fn add(&mut self, deal_score: DealScore) {
+        match deal_score.team {
+            Team::Lajvarna => self.lajvarna += deal_score.points,
+            Team::Gottarna => self.gottarna += deal_score.points,
+        }
+    }
+
+    pub fn leader(&self) -> Option<Team> {
+        match self.lajvarna.cmp(&self.gottarna) {
+            std::cmp::Ordering::Greater => Some(Team::Lajvarna),
+            std::cmp::Ordering::Less => Some(Team::Gottarna),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DealScore {
+    team: Team,
+    points: u16,
+}
+
+impl DealScore {
+    pub fn new(play_round: &PlayRound, table: &Table, scoring: ScoringScheme) -> Result<DealScore, GameError> {
+        if !play_round.is_finished() {
+            return Err(GameError::RoundNotFinished);
+        }
+
+        let (seat, points) = Self::score_seat(
+            play_round.contract(),
+            play_round.tricks_won_by_side(Seat::North),
+            scoring,
+        );
+
+        Ok(DealScore {
+            team: table.team_at(seat),
+            points,
+        })
+    }
+
+    pub fn team(&self) -> Team {
+        self.team
+    }
+
+    pub fn points(&self) -> u16 {
+        self.points
+    }
+
+    // Returns a seat of the scoring side together with the points it scored,
+    // given the tricks taken by north-south
+    fn score_seat(contract: Contract, north_south_tricks: usize, scoring: ScoringScheme) -> (Seat, u16) {
+        let side_tricks = |seat: Seat| match seat {
+            Seat::North | Seat::South => north_south_tricks,
+            Seat::East | Seat::West => 13 - north_south_tricks,
+        };
+
+        match contract {
+            // The bidders score their tricks beyond book,
+            // a failed grand gives the opponents their tricks beyond book,
+            // doubled under classic scoring
+            Contract::Grand { bidder } => {
+                let opponent = bidder.left_hand_opponent();
+                let penalty = match scoring {
+                    ScoringScheme::Classic => 2,
+                    ScoringScheme::Flat => 1,
+                };
+
+                if side_tricks(bidder) > BOOK {
+                    (bidder, (side_tricks(bidder) - BOOK) as u16)
+                } else {
+                    (opponent, ((side_tricks(opponent) - BOOK) * penalty) as u16)
+                }
+            }
+            // The side taking the fewest tricks scores the tricks
+            // its opponents were forced to take beyond book
+            Contract::Nolo => {
+                if side_tricks(Seat::North) > BOOK {
+                    (Seat::East, (side_tricks(Seat::North) - BOOK) as u16)
+                } else {
+                    (Seat::North, (side_tricks(Seat::East) - BOOK) as u16)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{Card, Rank, Suit};
+    use crate::hand::Hand;
+    use super::*;
+
+    // Every seat holds a full suit, so whoever is on lead takes every trick
+    fn play_out(contract: Contract, dealer: Seat) -> PlayRound {
+        let mut hands = vec![Hand::new(); 4];
+        for (hand, suit) in hands.iter_mut().zip(Suit::all()) {
+            Rank::all().into_iter().for_each(|rank| hand.add_card(Card::new(suit, rank)));
+        }
+        let mut play_round = PlayRound::new(hands, dealer, contract);
+
+        while let Some(seat) = play_round.turn() {
+            let card = play_round.legal_cards(seat)[0];
+            play_round.play_card(seat, card).unwrap();
+        }
+
+        play_round
+    }
+
+    #[test]
+    fn successful_grand_scores_tricks_beyond_book() {
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::East }, 4, ScoringScheme::Classic),
+            (Seat::East, 3),
+        );
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::South }, 7, ScoringScheme::Classic),
+            (Seat::South, 1),
+        );
+    }
+
+    #[test]
+    fn failed_grand_gives_opponents_double() {
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::North }, 6, ScoringScheme::Classic),
+            (Seat::East, 2),
+        );
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::West }, 10, ScoringScheme::Classic),
+            (Seat::North, 8),
+        );
+    }
+
+    #[test]
+    fn flat_scoring_does_not_double_failed_grand() {
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::West }, 10, ScoringScheme::Flat),
+            (Seat::North, 4),
+        );
+        assert_eq!(
+            DealScore::score_seat(Contract::Grand { bidder: Seat::South }, 7, ScoringScheme::Flat),
+            (Seat::South, 1),
+        );
+    }
+
+    #[test]
+    fn nolo_scores_for_side_with_fewest_tricks() {
+        assert_eq!(DealScore::score_seat(Contract::Nolo, 2, ScoringScheme::Classic), (Seat::North, 5));
+        assert_eq!(DealScore::score_seat(Contract::Nolo, 8, ScoringScheme::Classic), (Seat::East, 2));
+    }
+
+    #[test]
+    fn played_out_failed_grand() {
+        // East is on lead and takes every trick against the north-south bidders
+        let play_round = play_out(Contract::Grand { bidder: Seat::North }, Seat::North);
+
+        assert_eq!(play_round.tricks_won_by_side(Seat::East), 13);
+        assert_eq!(
+            DealScore::score_seat(
+                play_round.contract(),
+                play_round.tricks_won_by_side(Seat::North),
+                ScoringScheme::Classic,
+            ),
+            (Seat::East, 14),
+        );
+    }
+
+    #[test]
+    fn score_accumulates_per_team() {
+        let mut score = Score::default();
+
+        score.add(DealScore { team: Team::Lajvarna, points: 3 });
+        score.add(DealScore { team: Team::Gottarna, points: 2 });
+        score.add(DealScore { team: Team::Lajvarna, points: 1 });
+
+        assert_eq!(score.points(Team::Lajvarna), 4);
+        assert_eq!(score.points(Team::Gottarna), 2);
+        assert_eq!(score.leader(), Some(Team::Lajvarna));
+    }
+}
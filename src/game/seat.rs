@@ -0,0 +1,119 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Seat {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Seat {
+    // Clockwise, starting with north
+    pub fn all() -> Vec<Seat> {
+        vec![
+            Seat::North,
+            Seat::East,
+            Seat::South,
+            Seat::West,
+        ]
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Seat::North => 0,
+            Seat::East => 1,
+            Seat::South => 2,
+            Seat::West => 3,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Seat {
+        match index % 4 {
+            0 => Seat::North,
+            1 => Seat::East,
+            2 => Seat::South,
+            _ => Seat::West,
+        }
+    }
+
+    pub fn clockwise(&self) -> Seat {
+        Seat::from_index(self.index() + 1)
+    }
+
+    pub fn counter_clockwise(&self) -> Seat {
+        Seat::from_index(self.index() + 3)
+    }
+
+    pub fn partner(&self) -> Seat {
+        Seat::from_index(self.index() + 2)
+    }
+
+    pub fn left_hand_opponent(&self) -> Seat {
+        self.clockwise()
+    }
+
+    pub fn right_hand_opponent(&self) -> Seat {
+        self.counter_clockwise()
+    }
+
+    pub fn is_partner_of(&self, other: Seat) -> bool {
+        self.partner() == other
+    }
+
+    // Whether the seats belong to the same partnership, including the seat itself
+    pub fn same_side(&self, other: Seat) -> bool {
+        *self == other || self.is_partner_of(other)
+    }
+
+    // All four seats in play order, starting with this one
+    pub fn play_order(&self) -> Vec<Seat> {
+        (0..4).map(|offset| Seat::from_index(self.index() + offset)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clockwise() {
+        assert_eq!(Seat::North.clockwise(), Seat::East);
+        assert_eq!(Seat::West.clockwise(), Seat::North);
+        assert_eq!(Seat::North.counter_clockwise(), Seat::West);
+    }
+
+    #[test]
+    fn partner() {
+        assert_eq!(Seat::North.partner(), Seat::South);
+        assert_eq!(Seat::East.partner(), Seat::West);
+        assert!(Seat::West.is_partner_of(Seat::East));
+        assert!(!Seat::West.is_partner_of(Seat::North));
+    }
+
+    #[test]
+    fn opponents() {
+        assert_eq!(Seat::South.left_hand_opponent(), Seat::West);
+        assert_eq!(Seat::South.right_hand_opponent(), Seat::East);
+    }
+
+    #[test]
+    fn same_side() {
+        assert!(Seat::North.same_side(Seat::North));
+        assert!(Seat::North.same_side(Seat::South));
+        assert!(!Seat::North.same_side(Seat::East));
+    }
+
+    #[test]
+    fn index_round_trip() {
+        assert!(Seat::all().into_iter().all(|s| Seat::from_index(s.index()) == s));
+    }
+
+    #[test]
+    fn play_order() {
+        assert_eq!(
+            Seat::South.play_order(),
+            vec![Seat::South, Seat::West, Seat::North, Seat::East],
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use crate::card::Card;
+use crate::errors::GameError;
+use crate::game::bid_round::Bid;
+use crate::game::contract::Contract;
+use crate::game::lobby::Lobby;
+use crate::game::score::DealScore;
+use crate::game::seat::Seat;
+use crate::game::{Game, Phase, PhaseKind, Player, Settings, Team};
+use crate::user::{User, UserId};
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GameCommand {
+    Join,
+    Spectate,
+    Leave,
+    Ready,
+    Unready,
+    ChangeTeam(Team),
+    ChooseSeat(Seat),
+    Kick(UserId),
+    TransferHost(UserId),
+    Lock,
+    Unlock,
+    UpdateSettings(Settings),
+    Start,
+    Deal,
+    Bid(Bid),
+    PlayCard(Card),
+    Claim,
+    // Gives up the whole match, not just the deal, the deal in progress is never scored
+    Concede,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GameEvent {
+    // Names are kept so the users can be rebuilt from the log
+    Joined { user: UserId, name: String, team: Team },
+    SpectatorJoined { user: UserId, name: String },
+    Left { user: UserId },
+    Readied { user: UserId },
+    Unreadied { user: UserId },
+    TeamChanged { user: UserId, team: Team },
+    SeatChosen { user: UserId, seat: Seat },
+    HostChanged { user: Option<UserId> },
+    Locked,
+    Unlocked,
+    SettingsChanged(Settings),
+    // The card each user drew and the users in seat order
+    GameStarted { seed: u64, first_dealer: Seat, draws: Vec<(UserId, Card)>, seats: Vec<UserId> },
+    // The deal seed and the hands it gave, in seat order
+    Dealt { dealer: Seat, seed: u64, hands: Vec<Vec<Card>> },
+    BidMade { seat: Seat, bid: Bid },
+    ContractSettled(Contract),
+    Claimed { seat: Seat },
+    CardPlayed { seat: Seat, card: Card },
+    TrickWon { seat: Seat },
+    DealScored(DealScore),
+    Conceded { team: Team },
+    MatchWon { team: Team },
+}
+
+// The whole life of a game behind a single entry point,
+// from gathering players in the lobby to the end of the match
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Session {
+    Lobby(Lobby),
+    Game(Game),
+}
+
+impl Session {
+    pub fn new(settings: Settings) -> Session {
+        Session::Lobby(Lobby::new(settings))
+    }
+
+    pub fn lobby(&self) -> Option<&Lobby> {
+        match self {
+            Session::Lobby(lobby) => Some(lobby),
+            Session::Game(_) => None,
+        }
+    }
+
+    pub fn game(&self) -> Option<&Game> {
+        match self {
+            Session::Lobby(_) => None,
+            Session::Game(game) => Some(game),
+        }
+    }
+
+    pub fn phase(&self) -> PhaseKind {
+        match self {
+            Session::Lobby(_) => PhaseKind::Lobby,
+            Session::Game(game) => game.phase().kind(),
+        }
+    }
+
+    // The seat the game waits for, the lobby waits for its players rather than a seat
+    pub fn awaited(&self) -> Option<Seat> {
+        self.game().and_then(|game| game.awaited())
+    }
+
+    pub fn apply(&mut self, user: &Arc<User>, command: GameCommand) -> Result<Vec<GameEvent>, GameError> {
+        match self {
+            Session::Lobby(lobby) => {
+                let Some(events) = Self::apply_to_lobby(lobby, user, &command)? else {
+                    return Self::start(self, user);
+                };
+
+                Ok(events)
+            }
+            Session::Game(game) => Self::apply_to_game(game, user, command),
+        }
+    }
+
+    // Returns None when the command starts the game and the lobby has to be left behind
+    fn apply_to_lobby(lobby: &mut Lobby, user: &Arc<User>, command: &GameCommand) -> Result<Option<Vec<GameEvent>>, GameError> {
+        let id = user.id();
+        let host = Self::host_of(lobby);
+
+        let mut events = match command {
+            GameCommand::Join => {
+                lobby.add_user(user.clone())?;
+                let team = Self::member(lobby, id)?.team();
+
+                vec![GameEvent::Joined { user: id, name: user.name().to_string(), team }]
+            }
+            GameCommand::Spectate => {
+                lobby.add_spectator(user.clone())?;
+
+                vec![GameEvent::SpectatorJoined { user: id, name: user.name().to_string() }]
+            }
+            GameCommand::Leave => {
+                match lobby.spectators().iter().any(|s| s.id() == id) {
+                    true => lobby.del_spectator(user)?,
+                    false => lobby.del_user(user)?,
+                }
+
+                vec![GameEvent::Left { user: id }]
+            }
+            GameCommand::Ready => {
+                lobby.ready_up(user)?;
+
+                vec![GameEvent::Readied { user: id }]
+            }
+            GameCommand::Unready => {
+                lobby.unready(user)?;
+
+                vec![GameEvent::Unreadied { user: id }]
+            }
+            GameCommand::ChangeTeam(team) => {
+                lobby.change_team(user, *team)?;
+
+                vec![GameEvent::TeamChanged { user: id, team: *team }]
+            }
+            GameCommand::ChooseSeat(seat) => {
+                lobby.choose_seat(user, *seat)?;
+
+                vec![GameEvent::SeatChosen { user: id, seat: *seat }]
+            }
+            GameCommand::Kick(target) => {
+                let target_user = Self::member(lobby, *target)?.user().clone();
+                lobby.kick(user, &target_user)?;
+
+                vec![GameEvent::Left { user: *target }]
+            }
+            GameCommand::TransferHost(target) => {
+                let target_user = Self::member(lobby, *target)?.user().clone();
+                lobby.transfer_host(user, &target_user)?;
+
+                Vec::new()
+            }
+            GameCommand::Lock => {
+                lobby.lock(user)?;
+
+                vec![GameEvent::Locked]
+            }
+            GameCommand::Unlock => {
+                lobby.unlock(user)?;
+
+                vec![GameEvent::Unlocked]
+            }
+            GameCommand::UpdateSettings(settings) => {
+                lobby.update_settings(user, *settings)?;
+
+                vec![GameEvent::SettingsChanged(*settings)]
+            }
+            GameCommand::Start => return Ok(None),
+            GameCommand::Deal
+            | GameCommand::Bid(_)
+            | GameCommand::PlayCard(_)
+            | GameCommand::Claim
+            | GameCommand::Concede => return Err(GameError::NotStarted),
+        };
+
+        if Self::host_of(lobby) != host {
+            events.push(GameEvent::HostChanged { user: Self::host_of(lobby) });
+        }
+
+        Ok(Some(events))
+    }
+
+    // Only the host can start, the lobby is swapped for the game it starts
+    fn start(&mut self, user: &User) -> Result<Vec<GameEvent>, GameError> {
+        let Session::Lobby(lobby) = self else {
+            return Err(GameError::AlreadyStarted);
+        };
+
+        if Self::host_of(lobby) != Some(user.id()) {
+            return Err(GameError::NotHost);
+        }
+
+        let game = lobby.start_game()?;
+        let event = Self::game_started(&game);
+        *self = Session::Game(game);
+
+        Ok(vec![event])
+    }
+
+    fn apply_to_game(game: &mut Game, user: &Arc<User>, command: GameCommand) -> Result<Vec<GameEvent>, GameError> {
+        match command {
+            GameCommand::Spectate => {
+                game.add_spectator(user.clone())?;
+
+                return Ok(vec![GameEvent::SpectatorJoined { user: user.id(), name: user.name().to_string() }]);
+            }
+            GameCommand::Leave => {
+                // Players can't walk out of a started game, only concede it
+                if game.table().seat_of(user).is_some() {
+                    return Err(GameError::AlreadyStarted);
+                }
+                game.remove_spectator(user)?;
+
+                return Ok(vec![GameEvent::Left { user: user.id() }]);
+            }
+            _ => {}
+        }
+
+        let seat = game.seat_of(user)?;
+        let deals_played = game.history().len();
+
+        let mut events = match command {
+            GameCommand::Deal => {
+                game.start_round(seat)?;
+
+                vec![Self::dealt(game)]
+            }
+            GameCommand::Bid(bid) => {
+                let contract = game.register_bid(seat, bid)?;
+
+                std::iter::once(GameEvent::BidMade { seat, bid })
+                    .chain(contract.map(GameEvent::ContractSettled))
+                    .collect()
+            }
+            GameCommand::PlayCard(card) => {
+                let trick_winner = game.play_card(seat, card)?;
+
+                std::iter::once(GameEvent::CardPlayed { seat, card })
+                    .chain(trick_winner.map(|seat| GameEvent::TrickWon { seat }))
+                    .collect()
+            }
+            GameCommand::Claim => {
+                let plays = game.claim(seat)?;
+                let mut events = vec![GameEvent::Claimed { seat }];
+
+                // The claimer leads and wins every remaining trick
+                for (i, (player, card)) in plays.into_iter().enumerate() {
+                    events.push(GameEvent::CardPlayed { seat: player, card });
+                    if i % 4 == 3 {
+                        events.push(GameEvent::TrickWon { seat });
+                    }
+                }
+
+                events
+            }
+            GameCommand::Concede => {
+                let team = game.concede(seat)?;
+
+                vec![GameEvent::Conceded { team }]
+            }
+            _ => return Err(GameError::AlreadyStarted),
+        };
+
+        if game.history().len() > deals_played {
+            events.push(GameEvent::DealScored(game.history()[deals_played].deal_score()));
+        }
+
+        if let Some(team) = game.winner().filter(|_| {
+            events.iter().any(|e| matches!(e, GameEvent::DealScored(_) | GameEvent::Conceded { .. }))
+        }) {
+            events.push(GameEvent::MatchWon { team });
+        }
+
+        Ok(events)
+    }
+
+    fn game_started(game: &Game) -> GameEvent {
+        let table = game.table();
+
+        GameEvent::GameStarted {
+            seed: game.seed(),
+            first_dealer: table.first_dealer(),
+            draws: table.dealer_draw().draws().iter()
+                .map(|(p, c)| (p.user().id(), *c))
+                .collect(),
+            seats: Seat::all().into_iter()
+                .map(|seat| table.player(seat).user().id())
+                .collect(),
+        }
+    }
+
+    fn dealt(game: &Game) -> GameEvent {
+        let Phase::Bidding(bid_round) = game.phase() else {
+            unreachable!("A deal should start the bidding");
+        };
+
+        GameEvent::Dealt {
+            dealer: bid_round.dealer(),
+            seed: game.deal_seed(),
+            hands: Seat::all().into_iter()
+                .map(|seat| bid_round.hand(seat).cards())
+                .collect(),
+        }
+    }
+
+    // Rebuilds a session by folding its events in order, starting from the lobby settings.
+    // Everything random is replayed from the logged seeds and checked against the log.
+    pub fn from_events(settings: Settings, events: &[GameEvent]) -> Result<Session, GameError> {
+        let mut session = Session::new(settings);
+        events.iter().try_for_each(|event| session.fold(event))?;
+
+        Ok(session)
+    }
+
+    fn fold(&mut self, event: &GameEvent) -> Result<(), GameError> {
+        match self {
+            Session::Lobby(lobby) => {
+                if let GameEvent::GameStarted { seed, .. } = event {
+                    lobby.settings.seed = Some(*seed);
+                    let game = lobby.start_game()?;
+                    Self::verify(Self::game_started(&game) == *event)?;
+                    *self = Session::Game(game);
+
+                    return Ok(());
+                }
+
+                Self::fold_into_lobby(lobby, event)
+            }
+            Session::Game(game) => Self::fold_into_game(game, event),
+        }
+    }
+
+    fn fold_into_lobby(lobby: &mut Lobby, event: &GameEvent) -> Result<(), GameError> {
+        let host = || Self::host_of(lobby)
+            .and_then(|id| Self::user_in_lobby(lobby, id))
+            .ok_or(GameError::ReplayMismatch);
+
+        match event {
+            GameEvent::Joined { user, name, team } => {
+                lobby.add_user(Arc::new(User::with_id(*user, name)))?;
+                Self::verify(Self::member(lobby, *user)?.team() == *team)
+            }
+            GameEvent::SpectatorJoined { user, name } => {
+                lobby.add_spectator(Arc::new(User::with_id(*user, name)))
+            }
+            GameEvent::Left { user } => {
+                let user = Self::user_in_lobby(lobby, *user).ok_or(GameError::ReplayMismatch)?;
+                match lobby.spectators().iter().any(|s| s.id() == user.id()) {
+                    true => lobby.del_spectator(&user),
+                    false => lobby.del_user(&user),
+                }
+            }
+            GameEvent::Readied { user } => lobby.ready_up(&Self::user_with_id(*user)),
+            GameEvent::Unreadied { user } => lobby.unready(&Self::user_with_id(*user)),
+            GameEvent::TeamChanged { user, team } => lobby.change_team(&Self::user_with_id(*user), *team),
+            GameEvent::SeatChosen { user, seat } => lobby.choose_seat(&Self::user_with_id(*user), *seat),
+            // Leaving hands over the host by itself, only explicit transfers are left to redo
+            GameEvent::HostChanged { user: Some(user) } if Self::host_of(lobby) != Some(*user) => {
+                lobby.transfer_host(&host()?, &Self::user_with_id(*user))
+            }
+            GameEvent::HostChanged { .. } => Ok(()),
+            GameEvent::Locked => lobby.lock(&host()?),
+            GameEvent::Unlocked => lobby.unlock(&host()?),
+            GameEvent::SettingsChanged(settings) => lobby.update_settings(&host()?, *settings),
+            _ => Err(GameError::ReplayMismatch),
+        }
+    }
+
+    fn fold_into_game(game: &mut Game, event: &GameEvent) -> Result<(), GameError> {
+        match event {
+            GameEvent::SpectatorJoined { user, name } => {
+                game.add_spectator(Arc::new(User::with_id(*user, name)))
+            }
+            GameEvent::Left { user } => game.remove_spectator(&Self::user_with_id(*user)),
+            GameEvent::Dealt { dealer, .. } => {
+                game.start_round(*dealer)?;
+                Self::verify(Self::dealt(game) == *event)
+            }
+            GameEvent::BidMade { seat, bid } => game.register_bid(*seat, *bid).map(|_| ()),
+            GameEvent::ContractSettled(contract) => {
+                let Phase::Playing(play_round) = game.phase() else {
+                    return Err(GameError::ReplayMismatch);
+                };
+                Self::verify(play_round.contract() == *contract)
+            }
+            // The claimed tricks follow as ordinary card plays
+            GameEvent::Claimed { .. } => Ok(()),
+            GameEvent::CardPlayed { seat, card } => game.play_card(*seat, *card).map(|_| ()),
+            GameEvent::TrickWon { seat } => {
+                let last_trick = match game.phase() {
+                    Phase::Playing(play_round) => play_round.tricks().last(),
+                    _ => game.history().last().and_then(|round| round.play_round().tricks().last()),
+                };
+                Self::verify(last_trick.and_then(|trick| trick.winner()) == Some(*seat))
+            }
+            GameEvent::DealScored(deal_score) => {
+                Self::verify(game.history().last().map(|round| round.deal_score()) == Some(*deal_score))
+            }
+            GameEvent::Conceded { team } => {
+                let seat = game.table().seats_of(*team)[0];
+                game.concede(seat).map(|_| ())
+            }
+            GameEvent::MatchWon { team } => Self::verify(game.winner() == Some(*team)),
+            _ => Err(GameError::ReplayMismatch),
+        }
+    }
+
+    fn verify(matches: bool) -> Result<(), GameError> {
+        match matches {
+            true => Ok(()),
+            false => Err(GameError::ReplayMismatch),
+        }
+    }
+
+    // The lobby and game only compare users by id
+    fn user_with_id(id: UserId) -> User {
+        User::with_id(id, "")
+    }
+
+    fn user_in_lobby(lobby: &Lobby, id: UserId) -> Option<User> {
+        lobby.players().iter()
+            .map(|p| p.user())
+            .chain(lobby.spectators().iter().map(|s| s.as_ref()))
+            .find(|u| u.id() == id)
+            .cloned()
+    }
+
+    fn host_of(lobby: &Lobby) -> Option<UserId> {
+        lobby.host().map(|p| p.user().id())
+    }
+
+    fn member(lobby: &Lobby, user: UserId) -> Result<&Player, GameError> {
+        lobby.players().iter()
+            .find(|p| p.user().id() == user)
+            .ok_or(GameError::NotInLobby)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Phase;
+
+    fn setup_users() -> Vec<Arc<User>> {
+        vec![
+            Arc::new(User::new("A")),
+            Arc::new(User::new("B")),
+            Arc::new(User::new("C")),
+            Arc::new(User::new("D")),
+        ]
+    }
+
+    fn setup_session(users: &[Arc<User>]) -> Session {
+        let mut session = Session::new(Settings { seed: Some(5), ..Settings::default() });
+        users.iter().for_each(|u| { session.apply(u, GameCommand::Join).unwrap(); });
+        users.iter().for_each(|u| { session.apply(u, GameCommand::Ready).unwrap(); });
+        session.apply(&users[0], GameCommand::Start).unwrap();
+
+        session
+    }
+
+    fn user_at(session: &Session, users: &[Arc<User>], seat: Seat) -> Arc<User> {
+        let game = session.game().unwrap();
+
+        users.iter()
+            .find(|u| game.table().seat_of(u) == Some(seat))
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn lobby_commands_emit_events() {
+        let users = setup_users();
+        let mut session = Session::new(Settings::default());
+
+        assert_eq!(
+            session.apply(&users[0], GameCommand::Join),
+            Ok(vec![
+                GameEvent::Joined { user: users[0].id(), name: "A".to_string(), team: Team::Lajvarna },
+                GameEvent::HostChanged { user: Some(users[0].id()) },
+            ]),
+        );
+        assert_eq!(
+            session.apply(&users[1], GameCommand::Join),
+            Ok(vec![GameEvent::Joined { user: users[1].id(), name: "B".to_string(), team: Team::Gottarna }]),
+        );
+        assert_eq!(
+            session.apply(&users[0], GameCommand::Leave),
+            Ok(vec![
+                GameEvent::Left { user: users[0].id() },
+                GameEvent::HostChanged { user: Some(users[1].id()) },
+            ]),
+        );
+        assert_eq!(session.apply(&users[1], GameCommand::Lock), Ok(vec![GameEvent::Locked]));
+        assert_eq!(session.apply(&users[2], GameCommand::Join), Err(GameError::LobbyLocked));
+    }
+
+    #[test]
+    fn game_commands_are_refused_in_the_lobby() {
+        let users = setup_users();
+        let mut session = Session::new(Settings::default());
+        session.apply(&users[0], GameCommand::Join).unwrap();
+
+        assert_eq!(session.apply(&users[0], GameCommand::Bid(Bid::Play)), Err(GameError::NotStarted));
+        assert_eq!(session.apply(&users[0], GameCommand::Deal), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn only_the_host_starts_the_game() {
+        let users = setup_users();
+        let mut session = Session::new(Settings::default());
+        users.iter().for_each(|u| { session.apply(u, GameCommand::Join).unwrap(); });
+        users.iter().for_each(|u| { session.apply(u, GameCommand::Ready).unwrap(); });
+
+        assert_eq!(session.apply(&users[1], GameCommand::Start), Err(GameError::NotHost));
+        assert!(session.lobby().is_some());
+
+        let events = session.apply(&users[0], GameCommand::Start).unwrap();
+
+        assert!(matches!(events[..], [GameEvent::GameStarted { first_dealer: Seat::North, .. }]));
+        assert_eq!(session.apply(&users[0], GameCommand::Join), Err(GameError::AlreadyStarted));
+    }
+
+    #[test]
+    fn a_deal_is_played_through_commands() {
+        let users = setup_users();
+        let mut session = setup_session(&users);
+        let north = user_at(&session, &users, Seat::North);
+        let east = user_at(&session, &users, Seat::East);
+
+        assert_eq!(session.apply(&east, GameCommand::Deal), Err(GameError::NotPlayersTurn));
+
+        let events = session.apply(&north, GameCommand::Deal).unwrap();
+
+        assert!(matches!(events[..], [GameEvent::Dealt { dealer: Seat::North, .. }]));
+        assert_eq!(
+            session.apply(&east, GameCommand::Bid(Bid::Play)),
+            Ok(vec![
+                GameEvent::BidMade { seat: Seat::East, bid: Bid::Play },
+                GameEvent::ContractSettled(Contract::Grand { bidder: Seat::East }),
+            ]),
+        );
+
+        let mut events = Vec::new();
+        while let Phase::Playing(play_round) = session.game().unwrap().phase() {
+            let seat = play_round.turn().unwrap();
+            let card = play_round.legal_cards(seat)[0];
+            let user = user_at(&session, &users, seat);
+            events.extend(session.apply(&user, GameCommand::PlayCard(card)).unwrap());
+        }
+
+        let game = session.game().unwrap();
+        assert_eq!(events.iter().filter(|e| matches!(e, GameEvent::CardPlayed { .. })).count(), 52);
+        assert_eq!(events.iter().filter(|e| matches!(e, GameEvent::TrickWon { .. })).count(), 13);
+        assert_eq!(events.last(), Some(&GameEvent::DealScored(game.history()[0].deal_score())));
+    }
+
+    #[test]
+    fn session_moves_through_the_phases() {
+        let users = setup_users();
+        let session = Session::new(Settings::default());
+
+        assert_eq!(session.phase(), PhaseKind::Lobby);
+        assert_eq!(session.awaited(), None);
+
+        let session = setup_session(&users);
+
+        assert_eq!(session.phase(), PhaseKind::Seating);
+        assert_eq!(session.awaited(), Some(Seat::North));
+    }
+
+    #[test]
+    fn spectators_cant_act() {
+        let users = setup_users();
+        let mut session = setup_session(&users);
+        let spectator = Arc::new(User::new("Kibitzer"));
+
+        session.apply(&spectator, GameCommand::Spectate).unwrap();
+
+        assert_eq!(session.apply(&spectator, GameCommand::Deal), Err(GameError::Spectating));
+        assert_eq!(session.apply(&users[0], GameCommand::Leave), Err(GameError::AlreadyStarted));
+        assert_eq!(
+            session.apply(&spectator, GameCommand::Leave),
+            Ok(vec![GameEvent::Left { user: spectator.id() }]),
+        );
+    }
+
+    #[test]
+    fn conceding_ends_the_match() {
+        let users = setup_users();
+        let mut session = setup_session(&users);
+        let team = session.game().unwrap().table().team_at(Seat::North);
+        let north = user_at(&session, &users, Seat::North);
+
+        assert_eq!(
+            session.apply(&north, GameCommand::Concede),
+            Ok(vec![
+                GameEvent::Conceded { team },
+                GameEvent::MatchWon { team: team.opponent() },
+            ]),
+        );
+        assert!(session.game().unwrap().conceded());
+    }
+}
@@ -1,58 +1,235 @@
 use crate::card::Card;
 use crate::game::lobby::Lobby;
-use crate::game::Player;
+use crate::game::seat::Seat;
+use crate::game::{Player, SeatingMode, Team};
 use crate::deck::Deck;
+use crate::user::User;
+use rand::Rng;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct Table<'a> {
-    north: Player<'a>,
-    east: Player<'a>,
-    south: Player<'a>,
-    west: Player<'a>,
+// The cards drawn for the dealer button, highest first.
+// Cards are ordered by rank and then by suit (clubs, diamonds, hearts, spades),
+// so two different cards never tie.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::DealerDrawData"))]
+pub struct DealerDraw {
+    draws: Vec<(Player, Card)>,
 }
 
-impl<'a> Table<'a> {
-    pub fn new(lobby: &Lobby<'a>) -> Table<'a> {
+impl DealerDraw {
+    fn new(mut draws: Vec<(Player, Card)>) -> DealerDraw {
+        draws.sort_by(|a, b| b.1.compare_bridge_value(&a.1));
+
+        DealerDraw { draws }
+    }
+
+    pub fn draws(&self) -> &[(Player, Card)] {
+        &self.draws
+    }
+
+    pub fn card_of(&self, user: &User) -> Option<&Card> {
+        self.draws.iter()
+            .find(|(p, _)| p.user().id() == user.id())
+            .map(|(_, c)| c)
+    }
+
+    pub fn winner(&self) -> &Player {
+        &self.draws[0].0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::TableData"))]
+pub struct Table {
+    north: Player,
+    east: Player,
+    south: Player,
+    west: Player,
+    first_dealer: Seat,
+    dealer_draw: DealerDraw,
+}
+
+impl Table {
+    pub fn new(lobby: &Lobby) -> Table {
+        Self::with_rng(lobby, &mut rand::thread_rng())
+    }
+
+    pub fn with_rng<R: Rng + ?Sized>(lobby: &Lobby, rng: &mut R) -> Table {
         // Each player draws a card
-        let high_card_draws = Self::high_card_for_dealer_button(lobby);
-        let highest_card_team = high_card_draws[0].0.team();
+        let dealer_draw = Self::high_card_for_dealer_button(lobby, rng);
+
+        match lobby.settings.seating {
+            SeatingMode::Draw => Self::seat_by_draw(dealer_draw),
+            SeatingMode::Choose => Self::seat_by_choice(dealer_draw),
+        }
+    }
+
+    fn seat_by_draw(dealer_draw: DealerDraw) -> Table {
+        let highest_card_team = dealer_draw.winner().team();
 
         // Highest card becomes the dealer at the north position
         // Her team mate becomes the south position
-        let dealer_team: Vec<Player> = high_card_draws.iter()
+        let dealer_team: Vec<Player> = dealer_draw.draws().iter()
             .filter(|(p, _)| p.team() == highest_card_team)
             .map(|(p, _)| p.clone())
             .collect();
 
         // The player in the other team with the highest draw sits at the east position
         // Her team mate sits at the west position
-        let starting_team: Vec<Player> = high_card_draws.iter()
+        let starting_team: Vec<Player> = dealer_draw.draws().iter()
             .filter(|(p, _)| p.team() != highest_card_team)
             .map(|(p, _)| p.clone())
             .collect();
 
         Table {
-            north: dealer_team[0],
-            east: starting_team[0],
-            south: dealer_team[1],
-            west: starting_team[1],
+            north: dealer_team[0].seated(Seat::North),
+            east: starting_team[0].seated(Seat::East),
+            south: dealer_team[1].seated(Seat::South),
+            west: starting_team[1].seated(Seat::West),
+            first_dealer: Seat::North,
+            dealer_draw,
         }
     }
 
-    fn high_card_for_dealer_button(lobby: &Lobby<'a>) -> Vec<(Player<'a>, Card)> {
+    // The players keep the seats they chose, the highest card only decides who deals first
+    fn seat_by_choice(dealer_draw: DealerDraw) -> Table {
+        let player_at = |seat: Seat| dealer_draw.draws().iter()
+            .find(|(p, _)| p.seat() == Some(seat))
+            .map(|(p, _)| p.clone())
+            .expect("Every seat should be chosen");
+
+        Table {
+            north: player_at(Seat::North),
+            east: player_at(Seat::East),
+            south: player_at(Seat::South),
+            west: player_at(Seat::West),
+            first_dealer: dealer_draw.winner().seat().expect("Every seat should be chosen"),
+            dealer_draw,
+        }
+    }
+
+    pub fn dealer_draw(&self) -> &DealerDraw {
+        &self.dealer_draw
+    }
+
+    pub fn first_dealer(&self) -> Seat {
+        self.first_dealer
+    }
+
+    pub fn player(&self, seat: Seat) -> &Player {
+        match seat {
+            Seat::North => &self.north,
+            Seat::East => &self.east,
+            Seat::South => &self.south,
+            Seat::West => &self.west,
+        }
+    }
+
+    pub fn team_at(&self, seat: Seat) -> Team {
+        self.player(seat).team()
+    }
+
+    pub fn seat_of(&self, user: &User) -> Option<Seat> {
+        Seat::all().into_iter()
+            .find(|seat| self.player(*seat).user().id() == user.id())
+    }
+
+    pub fn seats_of(&self, team: Team) -> Vec<Seat> {
+        Seat::all().into_iter()
+            .filter(|seat| self.team_at(*seat) == team)
+            .collect()
+    }
+
+    // Every seat with its player in play order, starting with the given seat
+    pub fn players_from(&self, seat: Seat) -> impl Iterator<Item = (Seat, &Player)> {
+        seat.play_order().into_iter()
+            .map(move |s| (s, self.player(s)))
+    }
+
+    fn high_card_for_dealer_button<R: Rng + ?Sized>(lobby: &Lobby, rng: &mut R) -> DealerDraw {
         let mut deck = Deck::new();
-        deck.shuffle();
+        deck.shuffle_with(rng);
 
-        let mut player_cards: Vec<(Player, Card)> = lobby.players.iter().map(|p| {
+        let player_cards: Vec<(Player, Card)> = lobby.players.iter().map(|p| {
             let card = deck.cards.pop().expect("Deck should have enough cards");
-            ((*p).clone(), card)
+            (p.clone(), card)
         }).collect();
 
-        player_cards.sort_by(|a, b| {
-            a.1.compare_bridge_value(&b.1)
-        });
+        DealerDraw::new(player_cards)
+    }
+}
+
+// A table is only read back the way it can be set up, four different players who all
+// drew for the deal, each in their own seat, partners across and the highest card dealing
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::{DealerDraw, Table};
+    use crate::card::Card;
+    use crate::errors::GameError;
+    use crate::game::seat::Seat;
+    use crate::game::Player;
+    use crate::user::UserId;
+
+    #[derive(serde::Deserialize)]
+    pub(super) struct DealerDrawData {
+        draws: Vec<(Player, Card)>,
+    }
+
+    impl TryFrom<DealerDrawData> for DealerDraw {
+        type Error = GameError;
+
+        fn try_from(data: DealerDrawData) -> Result<DealerDraw, GameError> {
+            if data.draws.len() != 4 {
+                return Err(GameError::InvalidTable);
+            }
+
+            Ok(DealerDraw::new(data.draws))
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    pub(super) struct TableData {
+        north: Player,
+        east: Player,
+        south: Player,
+        west: Player,
+        first_dealer: Seat,
+        dealer_draw: DealerDraw,
+    }
+
+    impl TryFrom<TableData> for Table {
+        type Error = GameError;
+
+        fn try_from(data: TableData) -> Result<Table, GameError> {
+            let table = Table {
+                north: data.north,
+                east: data.east,
+                south: data.south,
+                west: data.west,
+                first_dealer: data.first_dealer,
+                dealer_draw: data.dealer_draw,
+            };
+
+            let ids: Vec<UserId> = Seat::all().into_iter()
+                .map(|seat| table.player(seat).user().id())
+                .collect();
+            let different = ids.iter().enumerate().all(|(i, id)| !ids[..i].contains(id));
+            let drawn = ids.iter()
+                .all(|id| table.dealer_draw.draws().iter().any(|(p, _)| p.user().id() == *id));
+            let seated = Seat::all().into_iter().all(|seat| {
+                table.player(seat).seat() == Some(seat)
+                    && table.team_at(seat) == table.team_at(seat.partner())
+                    && table.team_at(seat) != table.team_at(seat.clockwise())
+            });
+            let dealer = table.player(table.first_dealer).user().id() == table.dealer_draw.winner().user().id();
 
-        player_cards
+            if !(different && drawn && seated && dealer) {
+                return Err(GameError::InvalidTable);
+            }
+
+            Ok(table)
+        }
     }
 }
 
@@ -60,20 +237,21 @@ impl<'a> Table<'a> {
 mod tests {
     use super::*;
     use crate::game::lobby::Lobby;
-    use crate::game::{Settings};
+    use crate::game::{SeatingMode, Settings};
     use crate::user::User;
+    use std::sync::Arc;
 
-    fn setup_users() -> Vec<User> {
+    fn setup_users() -> Vec<Arc<User>> {
         vec![
-            User::new("A"),
-            User::new("B"),
-            User::new("C"),
-            User::new("D"),
+            Arc::new(User::new("A")),
+            Arc::new(User::new("B")),
+            Arc::new(User::new("C")),
+            Arc::new(User::new("D")),
         ]
     }
 
-    fn setup_lobby() -> Lobby<'static> {
-        let settings = Settings { to_win: 13 };
+    fn setup_lobby() -> Lobby {
+        let settings = Settings { to_win: 13, seed: None, ..Settings::default() };
         Lobby::new(settings)
     }
 
@@ -81,16 +259,16 @@ mod tests {
     fn high_card_for_dealer_button() {
         let users = setup_users();
         let mut lobby = setup_lobby();
-        users.iter().for_each(|u| lobby.add_user(u));
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
 
-        let high_card_draws = Table::high_card_for_dealer_button(&lobby);
+        let high_card_draws = Table::high_card_for_dealer_button(&lobby, &mut rand::thread_rng());
 
-        let cards = high_card_draws.iter()
-            .map(|(_, c)| c.clone())
+        let cards = high_card_draws.draws().iter()
+            .map(|(_, c)| *c)
             .collect::<Vec<Card>>();
 
         let mut cards_sorted_by_bridge_rank = cards.clone();
-        cards_sorted_by_bridge_rank.sort_by(|a, b| a.compare_bridge_value(b));
+        cards_sorted_by_bridge_rank.sort_by(|a, b| b.compare_bridge_value(a));
 
         assert_eq!(
             cards,
@@ -98,15 +276,163 @@ mod tests {
         );
     }
 
+    #[test]
+    fn highest_card_wins_the_dealer_button() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+        let draw = table.dealer_draw();
+        let highest = draw.draws().iter()
+            .max_by(|a, b| a.1.compare_bridge_value(&b.1))
+            .unwrap();
+
+        assert_eq!(draw.winner(), &highest.0);
+        assert_eq!(table.player(Seat::North).user(), highest.0.user());
+    }
+
+    #[test]
+    fn card_drawn_by_user() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+
+        assert!(users.iter().all(|u| table.dealer_draw().card_of(u).is_some()));
+        assert_eq!(table.dealer_draw().card_of(&User::new("Stranger")), None);
+    }
+
+    #[test]
+    fn seating_is_reproducible_with_a_seeded_rng() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table1 = Table::with_rng(&lobby, &mut StdRng::seed_from_u64(42));
+        let table2 = Table::with_rng(&lobby, &mut StdRng::seed_from_u64(42));
+
+        assert_eq!(table1, table2);
+    }
+
     #[test]
     fn new_table() {
         let users = setup_users();
         let mut lobby = setup_lobby();
-        users.iter().for_each(|u| lobby.add_user(u));
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
 
         let table = Table::new(&lobby);
 
         assert_eq!(table.north.team(), table.south.team());
         assert_eq!(table.east.team(), table.west.team());
     }
+
+    #[test]
+    fn player_at_seat() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+
+        assert_eq!(table.player(Seat::North), &table.north);
+        assert_eq!(table.player(Seat::West), &table.west);
+        assert_eq!(table.team_at(Seat::South), table.north.team());
+        assert_eq!(table.team_at(Seat::West), table.east.team());
+    }
+
+    #[test]
+    fn seat_of_user() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+
+        assert!(users.iter().all(|u| {
+            table.seat_of(u).map(|seat| table.player(seat).user()) == Some(u)
+        }));
+        assert_eq!(table.seat_of(&User::new("Stranger")), None);
+    }
+
+    #[test]
+    fn seats_of_team() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+
+        assert_eq!(table.seats_of(table.north.team()), vec![Seat::North, Seat::South]);
+        assert_eq!(table.seats_of(table.east.team()), vec![Seat::East, Seat::West]);
+    }
+
+    #[test]
+    fn players_in_play_order() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+        let order: Vec<(Seat, &Player)> = table.players_from(Seat::West).collect();
+
+        assert_eq!(
+            order,
+            vec![
+                (Seat::West, &table.west),
+                (Seat::North, &table.north),
+                (Seat::East, &table.east),
+                (Seat::South, &table.south),
+            ],
+        );
+    }
+
+    #[test]
+    fn drawn_seats_are_recorded_on_players() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+
+        let table = Table::new(&lobby);
+
+        assert!(Seat::all().into_iter().all(|seat| table.player(seat).seat() == Some(seat)));
+        assert_eq!(table.first_dealer(), Seat::North);
+    }
+
+    #[test]
+    fn chosen_seats_are_kept() {
+        let users = setup_users();
+        let mut lobby = Lobby::new(Settings { to_win: 13, seed: None, seating: SeatingMode::Choose, ..Settings::default() });
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let seats = vec![Seat::West, Seat::South, Seat::East, Seat::North];
+        users.iter().zip(seats.iter()).for_each(|(u, seat)| lobby.choose_seat(u, *seat).unwrap());
+
+        let table = Table::new(&lobby);
+        let draw_winner = table.dealer_draw().winner().user();
+
+        assert!(users.iter().zip(seats).all(|(u, seat)| table.seat_of(u) == Some(seat)));
+        assert_eq!(table.seat_of(draw_winner), Some(table.first_dealer()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reading_a_broken_table_is_an_error() {
+        let users = setup_users();
+        let mut lobby = setup_lobby();
+        users.iter().for_each(|u| lobby.add_user(u.clone()).unwrap());
+        let table = Table::new(&lobby);
+        let json = serde_json::to_string(&table).unwrap();
+        let mut wrong_seat: serde_json::Value = serde_json::from_str(&json).unwrap();
+        wrong_seat["north"]["seat"] = "east".into();
+        let mut no_draws: serde_json::Value = serde_json::from_str(&json).unwrap();
+        no_draws["dealer_draw"]["draws"] = serde_json::json!([]);
+
+        assert_eq!(serde_json::from_str::<Table>(&json).unwrap(), table);
+        assert!(serde_json::from_value::<Table>(wrong_seat).is_err());
+        assert!(serde_json::from_value::<Table>(no_draws).is_err());
+    }
 }
@@ -0,0 +1,218 @@
+use crate::card::Card;
+use crate::game::bid_round::Bid;
+use crate::game::contract::Contract;
+use crate::game::play_round::{PlayRound, Trick};
+use crate::game::score::Score;
+use crate::game::seat::Seat;
+use crate::game::{Game, Phase, SpectatorMode};
+use crate::hand::Hand;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HandView {
+    // Only the number of cards left is known
+    Hidden { cards: usize },
+    Visible(Hand),
+}
+
+impl HandView {
+    pub fn len(&self) -> usize {
+        match self {
+            HandView::Hidden { cards } => *cards,
+            HandView::Visible(hand) => hand.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// What the player in a seat gets to see of the game,
+// other hands are only shown as the number of cards left in them
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerView {
+    pub seat: Seat,
+    pub dealer: Seat,
+    pub bids: Vec<(Seat, Bid)>,
+    pub contract: Option<Contract>,
+    // Indexed by seat, only the viewer's own hand is visible
+    pub hands: Vec<HandView>,
+    pub tricks: Vec<Trick>,
+    pub current_trick: Option<Trick>,
+    pub turn: Option<Seat>,
+    // Empty unless it is the viewer's turn to play
+    pub legal_cards: Vec<Card>,
+    pub score: Score,
+}
+
+impl PlayerView {
+    pub(crate) fn new(game: &Game, seat: Seat) -> PlayerView {
+        let mut view = PlayerView {
+            seat,
+            dealer: game.dealer(),
+            bids: Vec::new(),
+            contract: None,
+            hands: Vec::new(),
+            tricks: Vec::new(),
+            current_trick: None,
+            turn: None,
+            legal_cards: Vec::new(),
+            score: game.score(),
+        };
+
+        let hands = match game.phase() {
+            Phase::Bidding(bid_round) => {
+                view.bids = bid_round.bids().to_vec();
+                view.turn = bid_round.turn();
+
+                Seat::all().into_iter().map(|s| bid_round.hand(s)).collect()
+            }
+            Phase::Playing(play_round) => {
+                view.bids = play_round.bids().to_vec();
+                view.contract = Some(play_round.contract());
+                view.tricks = play_round.tricks().to_vec();
+                view.current_trick = Some(play_round.current_trick().clone());
+                view.turn = play_round.turn();
+                view.legal_cards = play_round.legal_cards(seat);
+
+                play_round.hands().iter().collect()
+            }
+            _ => Vec::new(),
+        };
+
+        view.hands = hands.into_iter()
+            .zip(Seat::all())
+            .map(|(hand, s)| match s == seat {
+                true => HandView::Visible(hand.clone()),
+                false => HandView::Hidden { cards: hand.len() },
+            })
+            .collect();
+
+        view
+    }
+
+    pub fn hand(&self) -> Option<&Hand> {
+        match self.hands.get(self.seat.index()) {
+            Some(HandView::Visible(hand)) => Some(hand),
+            _ => None,
+        }
+    }
+
+    // Every card that has left a hand this deal, in the order it was played
+    pub fn played_cards(&self) -> Vec<Card> {
+        self.tricks.iter()
+            .chain(self.current_trick.iter())
+            .flat_map(|trick| trick.cards().to_vec())
+            .collect()
+    }
+}
+
+// What a spectator gets to see of the game
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpectatorView {
+    pub dealer: Seat,
+    pub bids: Vec<(Seat, Bid)>,
+    pub contract: Option<Contract>,
+    // Indexed by seat
+    pub hands: Vec<HandView>,
+    pub tricks: Vec<Trick>,
+    pub current_trick: Option<Trick>,
+    pub turn: Option<Seat>,
+    pub score: Score,
+}
+
+impl SpectatorView {
+    pub(crate) fn new(game: &Game) -> SpectatorView {
+        let mut view = SpectatorView {
+            dealer: game.dealer(),
+            bids: Vec::new(),
+            contract: None,
+            hands: Vec::new(),
+            tricks: Vec::new(),
+            current_trick: None,
+            turn: None,
+            score: game.score(),
+        };
+
+        match game.phase() {
+            Phase::Bidding(bid_round) => {
+                view.bids = bid_round.bids().to_vec();
+                view.turn = bid_round.turn();
+                view.hands = Seat::all().into_iter()
+                    .map(|seat| {
+                        let hand = bid_round.hand(seat);
+                        match game.settings().spectators {
+                            SpectatorMode::Kibitzer { delay: 0 } => HandView::Visible(hand.clone()),
+                            _ => HandView::Hidden { cards: hand.len() },
+                        }
+                    })
+                    .collect();
+            }
+            Phase::Playing(play_round) => view.show_play(game, play_round),
+            // Until the next deal the finished one stays on show, so a delayed kibitzer
+            // still gets to see the last tricks of it
+            Phase::DealScored(_) => {
+                if let Some(round) = game.history().last() {
+                    view.show_play(game, round.play_round());
+                }
+            }
+            _ => {}
+        }
+
+        view
+    }
+
+    fn show_play(&mut self, game: &Game, play_round: &PlayRound) {
+        self.dealer = play_round.dealer();
+        self.bids = play_round.bids().to_vec();
+        self.contract = Some(play_round.contract());
+        self.turn = play_round.turn();
+
+        match game.settings().spectators {
+            SpectatorMode::Hidden => self.show_live(play_round, false),
+            SpectatorMode::Kibitzer { delay: 0 } => self.show_live(play_round, true),
+            SpectatorMode::Kibitzer { delay } => self.show_delayed(play_round, delay as usize),
+        }
+    }
+
+    fn show_live(&mut self, play_round: &PlayRound, show_hands: bool) {
+        self.hands = play_round.hands().iter()
+            .map(|hand| match show_hands {
+                true => HandView::Visible(hand.clone()),
+                false => HandView::Hidden { cards: hand.len() },
+            })
+            .collect();
+        self.tricks = play_round.tricks().to_vec();
+        self.current_trick = Some(play_round.current_trick().clone());
+    }
+
+    // Shows the deal as it stood the given number of tricks ago,
+    // the hands stay hidden until the play has got that far
+    fn show_delayed(&mut self, play_round: &PlayRound, delay: usize) {
+        self.turn = Some(play_round.contract().opening_leader(play_round.dealer()));
+
+        let Some(shown) = play_round.tricks().len().checked_sub(delay) else {
+            self.hands = vec![HandView::Hidden { cards: 13 }; 4];
+            return;
+        };
+
+        // Cards played since are put back in the hands they came from
+        let mut hands = play_round.hands().to_vec();
+        let played_since: Vec<(Seat, Card)> = play_round.tricks()[shown..].iter()
+            .chain(std::iter::once(play_round.current_trick()))
+            .flat_map(|trick| trick.plays())
+            .collect();
+        played_since.into_iter()
+            .for_each(|(seat, card)| hands[seat.index()].add_card(card));
+
+        self.hands = hands.into_iter().map(HandView::Visible).collect();
+        self.tricks = play_round.tricks()[..shown].to_vec();
+        if let Some(winner) = self.tricks.last().and_then(|trick| trick.winner()) {
+            self.turn = Some(winner);
+        }
+    }
+}
@@ -1,27 +1,180 @@
-use crate::card::Card;
+use crate::card::{Card, Notation, Rank, Suit};
+use crate::card_set::CardSet;
+use crate::errors::{GameError, ParseCardError};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand {
-    cards: Vec<Card>,
+    cards: CardSet,
 }
 
 impl Hand {
     pub fn new() -> Hand {
         Hand {
-            cards: Vec::with_capacity(13),
+            cards: CardSet::new(),
         }
     }
 
     pub fn add_card(&mut self, card: Card) {
-        self.cards.push(card);
+        self.cards.insert(card);
+    }
+
+    pub fn remove_card(&mut self, card: &Card) -> Option<Card> {
+        self.cards.remove(*card).then_some(*card)
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.cards.contains(*card)
+    }
+
+    pub fn has_suit(&self, suit: Suit) -> bool {
+        self.cards.has_suit(suit)
+    }
+
+    // A player must follow the led suit if able, otherwise any card may be played
+    pub fn legal_set(&self, led_suit: Option<Suit>) -> CardSet {
+        match led_suit {
+            Some(suit) if self.has_suit(suit) => self.cards.suit(suit),
+            _ => self.cards,
+        }
+    }
+
+    pub fn legal_cards(&self, led_suit: Option<Suit>) -> Vec<Card> {
+        self.legal_set(led_suit).iter().collect()
+    }
+
+    pub fn check_play(&self, card: &Card, led_suit: Option<Suit>) -> Result<(), GameError> {
+        if !self.contains(card) {
+            return Err(GameError::CardNotInHand);
+        }
+
+        match led_suit {
+            Some(suit) if *card.suit() != suit && self.has_suit(suit) => {
+                Err(GameError::MustFollowSuit)
+            }
+            _ => Ok(()),
+        }
     }
 
     pub fn len(&self) -> usize {
         self.cards.len()
     }
 
-    pub fn cards(&self) -> &Vec<Card> {
-        &self.cards
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    // Sorted by suit, then by rank
+    pub fn cards(&self) -> Vec<Card> {
+        self.cards.iter().collect()
+    }
+
+    pub fn card_set(&self) -> CardSet {
+        self.cards
+    }
+
+    // Grouped by suit from spades down to clubs, highest rank first, a void is shown as "-"
+    pub fn format(&self, notation: Notation) -> String {
+        let groups: Vec<String> = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs].into_iter()
+            .map(|suit| {
+                let ranks: Vec<&str> = self.cards.suit(suit).iter()
+                    .rev()
+                    .map(|card| card.rank().format(notation))
+                    .collect();
+                let ranks = match ranks.is_empty() {
+                    true => "-".to_string(),
+                    false if notation == Notation::Swedish => ranks.join(" "),
+                    false => ranks.concat(),
+                };
+
+                match notation {
+                    Notation::Ascii => format!("{}:{}", suit.format(notation), ranks),
+                    Notation::Unicode => format!("{}{}", suit.format(notation), ranks),
+                    Notation::Swedish => format!("{}: {}", suit.format(notation), ranks),
+                }
+            })
+            .collect();
+
+        match notation {
+            Notation::Swedish => groups.join(", "),
+            _ => groups.join(" "),
+        }
+    }
+
+    // Each word is a rank, like "ess kung", or a run of one symbol ranks, like "AKT4"
+    fn parse_ranks(text: &str) -> Result<Vec<Rank>, ParseCardError> {
+        let mut ranks = Vec::new();
+
+        for word in text.split_whitespace().filter(|word| *word != "-") {
+            match word.parse() {
+                Ok(rank) => ranks.push(rank),
+                Err(_) => {
+                    for symbol in word.replace("10", "T").chars() {
+                        ranks.push(symbol.to_string().parse()?);
+                    }
+                }
+            }
+        }
+
+        Ok(ranks)
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(Notation::Ascii))
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseCardError;
+
+    // Reads the suit groups of any notation, the suits may come in any order
+    fn from_str(text: &str) -> Result<Hand, ParseCardError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ParseCardError::Empty);
+        }
+
+        // A word with a colon starts a group, and since Swedish ranks are separated by
+        // spaces the words after it belong to that group. Without colons every word
+        // is a group of its own, led by the suit.
+        let mut groups: Vec<(&str, String)> = Vec::new();
+        let mut named = false;
+        for word in text.split(|c: char| c == ',' || c.is_whitespace()).filter(|word| !word.is_empty()) {
+            if let Some((suit, ranks)) = word.split_once(':') {
+                groups.push((suit, ranks.to_string()));
+                named = true;
+            } else if let (true, Some((_, ranks))) = (named, groups.last_mut()) {
+                ranks.push(' ');
+                ranks.push_str(word);
+            } else {
+                let first = word.chars().next().ok_or(ParseCardError::Empty)?;
+                let (suit, ranks) = word.split_at(first.len_utf8());
+                groups.push((suit, ranks.to_string()));
+            }
+        }
+
+        let mut hand = Hand::new();
+        for (suit, ranks) in groups {
+            let suit: Suit = suit.parse()?;
+
+            for rank in Self::parse_ranks(&ranks)? {
+                if !hand.cards.insert(Card::new(suit, rank)) {
+                    return Err(ParseCardError::DuplicateCard);
+                }
+            }
+        }
+
+        Ok(hand)
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -75,4 +228,134 @@ mod tests {
 
         assert_eq!(hand.len(), 1);
     }
+
+    #[test]
+    fn remove_card_from_hand() {
+        let mut hand = Hand::new();
+        hand.add_card(Card::new(Suit::Spades, Rank::Ace));
+        hand.add_card(Card::new(Suit::Hearts, Rank::Two));
+
+        let removed = hand.remove_card(&Card::new(Suit::Spades, Rank::Ace));
+
+        assert_eq!(removed, Some(Card::new(Suit::Spades, Rank::Ace)));
+        assert_eq!(hand.cards(), vec![Card::new(Suit::Hearts, Rank::Two)]);
+    }
+
+    fn setup_hand() -> Hand {
+        let mut hand = Hand::new();
+        hand.add_card(Card::new(Suit::Spades, Rank::Ace));
+        hand.add_card(Card::new(Suit::Hearts, Rank::Two));
+        hand.add_card(Card::new(Suit::Spades, Rank::Four));
+
+        hand
+    }
+
+    #[test]
+    fn has_suit() {
+        let hand = setup_hand();
+
+        assert!(hand.has_suit(Suit::Spades));
+        assert!(!hand.has_suit(Suit::Clubs));
+    }
+
+    #[test]
+    fn all_cards_are_legal_when_leading() {
+        let hand = setup_hand();
+
+        assert_eq!(hand.legal_cards(None), hand.cards());
+    }
+
+    #[test]
+    fn only_led_suit_is_legal_when_able_to_follow() {
+        let hand = setup_hand();
+
+        assert_eq!(
+            hand.legal_cards(Some(Suit::Spades)),
+            vec![
+                Card::new(Suit::Spades, Rank::Four),
+                Card::new(Suit::Spades, Rank::Ace),
+            ],
+        );
+    }
+
+    #[test]
+    fn all_cards_are_legal_when_void_in_led_suit() {
+        let hand = setup_hand();
+
+        assert_eq!(hand.legal_cards(Some(Suit::Diamonds)), hand.cards());
+    }
+
+    #[test]
+    fn check_play_denies_breaking_follow_suit() {
+        let hand = setup_hand();
+
+        assert_eq!(
+            hand.check_play(&Card::new(Suit::Hearts, Rank::Two), Some(Suit::Spades)),
+            Err(GameError::MustFollowSuit),
+        );
+        assert_eq!(
+            hand.check_play(&Card::new(Suit::Spades, Rank::Four), Some(Suit::Spades)),
+            Ok(()),
+        );
+        assert_eq!(
+            hand.check_play(&Card::new(Suit::Hearts, Rank::Two), Some(Suit::Clubs)),
+            Ok(()),
+        );
+    }
+
+    #[test]
+    fn check_play_denies_card_not_in_hand() {
+        let hand = setup_hand();
+
+        assert_eq!(
+            hand.check_play(&Card::new(Suit::Clubs, Rank::Two), None),
+            Err(GameError::CardNotInHand),
+        );
+    }
+
+    #[test]
+    fn remove_card_not_in_hand() {
+        let mut hand = Hand::new();
+        hand.add_card(Card::new(Suit::Hearts, Rank::Two));
+
+        assert_eq!(hand.remove_card(&Card::new(Suit::Spades, Rank::Ace)), None);
+        assert_eq!(hand.len(), 1);
+    }
+
+    #[test]
+    fn formats_grouped_by_suit() {
+        let mut hand = setup_hand();
+        hand.add_card(Card::new(Suit::Spades, Rank::Ten));
+
+        assert_eq!(hand.to_string(), "S:AT4 H:2 D:- C:-");
+        assert_eq!(hand.format(Notation::Unicode), "♠AT4 ♥2 ♦- ♣-");
+        assert_eq!(hand.format(Notation::Swedish), "spader: ess tio fyra, hjärter: två, ruter: -, klöver: -");
+    }
+
+    #[test]
+    fn parses_grouped_hands() {
+        let mut hand = setup_hand();
+        hand.add_card(Card::new(Suit::Spades, Rank::Ten));
+
+        assert_eq!("S:AT4 H:2 D:- C:-".parse(), Ok(hand.clone()));
+        assert_eq!("h2 s104a".parse(), Ok(hand.clone()));
+        assert_eq!("♠AT4 ♥2 ♦- ♣-".parse(), Ok(hand.clone()));
+        assert_eq!("spader: ess tio fyra, hjärter: två".parse(), Ok(hand.clone()));
+        assert_eq!("spader: ess tio fyra hjärter: två".parse(), Ok(hand.clone()));
+        assert_eq!(
+            "spader: ess kung".parse::<Hand>().map(|hand| hand.cards()),
+            Ok(vec![Card::new(Suit::Spades, Rank::King), Card::new(Suit::Spades, Rank::Ace)]),
+        );
+        for notation in [Notation::Ascii, Notation::Unicode, Notation::Swedish] {
+            assert_eq!(hand.format(notation).parse(), Ok(hand.clone()));
+        }
+    }
+
+    #[test]
+    fn denies_parsing_bad_hands() {
+        assert_eq!("".parse::<Hand>(), Err(ParseCardError::Empty));
+        assert_eq!("S:AA".parse::<Hand>(), Err(ParseCardError::DuplicateCard));
+        assert_eq!("X:A".parse::<Hand>(), Err(ParseCardError::InvalidSuit));
+        assert_eq!("S:AZ".parse::<Hand>(), Err(ParseCardError::InvalidRank));
+    }
 }
@@ -1,6 +1,8 @@
 pub mod user;
 pub mod game;
-mod errors;
-mod card;
-mod deck;
-mod hand;
+pub mod errors;
+pub mod card;
+pub mod card_set;
+pub mod deal;
+pub mod deck;
+pub mod hand;
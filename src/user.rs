@@ -1,11 +1,29 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserId(pub u64);
+
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct User {
+    id: UserId,
     name: String,
 }
 
 impl User {
+    // Hands out a random 64-bit id. It doesn't depend on the process or on what came
+    // before, so it won't clash with ids from earlier runs or ids given to with_id
     pub fn new(name: &str) -> User {
-        User { name: name.to_string() }
+        User::with_id(UserId(rand::random()), name)
+    }
+
+    // For users whose id is kept elsewhere, e.g. in a server's user database.
+    // The caller makes sure the id is not given to anyone else
+    pub fn with_id(id: UserId, name: &str) -> User {
+        User { id, name: name.to_string() }
+    }
+
+    pub fn id(&self) -> UserId {
+        self.id
     }
 
     pub fn name(&self) -> &str {
@@ -28,4 +46,29 @@ mod tests {
         let user = User::new("Jane Doe");
         assert_eq!(user.name(), "Jane Doe");
     }
+
+    #[test]
+    fn new_users_get_unique_ids() {
+        let user1 = User::new("John Doe");
+        let user2 = User::new("John Doe");
+
+        assert_ne!(user1.id(), user2.id());
+        assert_ne!(user1, user2);
+    }
+
+    #[test]
+    fn new_ids_dont_follow_supplied_ones() {
+        let rebuilt = User::with_id(UserId(1), "From the log");
+        let newcomer = User::new("Newcomer");
+
+        assert_ne!(rebuilt.id(), newcomer.id());
+        assert_ne!(newcomer.id(), UserId(2));
+    }
+
+    #[test]
+    fn user_with_id() {
+        let user = User::with_id(UserId(42), "Jane Doe");
+
+        assert_eq!(user.id(), UserId(42));
+    }
 }